@@ -55,19 +55,39 @@ impl From<std::io::Error> for HttpError {
     }
 }
 
-#[derive(ThisError, Debug)]
+#[derive(ThisError, Debug, Clone)]
 pub enum RegisterError {
-    #[error("invalid pattern: {0}")]
-    InvalidPattern(#[from] PatternError),
+    #[error("invalid pattern {0:?}: {1}")]
+    InvalidPattern(String, #[source] PatternError),
     #[error("pattern {0} already registered ")]
     DuplicatePattern(Pattern),
     #[error("conflicting dynamic segment: expected {{{0}}} but got {{{1}}}")]
     DuplicateDynamicSegment(String, String),
     #[error("conflicting wildcard segment")]
     DuplicateWildcardSegment,
+    #[error("route name {0:?} already used by another pattern")]
+    DuplicateRouteName(String),
+    #[error("{0} nested in a {1} route can never match")]
+    ConflictingMethod(Pattern, String),
+    #[error("invalid guard: {0}")]
+    InvalidGuard(#[source] PatternError),
+    #[error("virtual hosts of the router mounted for {0:?} are never looked up")]
+    NestedVirtualHost(String),
 }
 
-#[derive(ThisError, Debug)]
+#[derive(ThisError, Debug, Clone)]
+pub enum UrlError {
+    #[error("no route named {0:?}")]
+    UnknownRoute(String),
+    #[error("missing url param {0:?}")]
+    MissingParam(String),
+    #[error("invalid url params: {0}")]
+    InvalidParams(String),
+    #[error("url param {0:?} does not satisfy its constraint {1:?}")]
+    ConstraintMismatch(String, String),
+}
+
+#[derive(ThisError, Debug, Clone)]
 pub enum PatternError {
     #[error("unsupported or invalid method")]
     UnsupportedMethod,
@@ -83,4 +103,6 @@ pub enum PatternError {
     WildcardPosition,
     #[error("invalid dynamic pattern definition")]
     InvalidDynamic,
+    #[error("invalid segment constraint {0:?}, expected int, uuid or a regex")]
+    InvalidConstraint(String),
 }
\ No newline at end of file
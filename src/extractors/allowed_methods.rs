@@ -0,0 +1,38 @@
+use futures::future::BoxFuture;
+use http_tokio::extensions::Extension;
+use std::ops::Deref;
+use crate::{extractors::FromRequest, result::HttpResult};
+
+/// Methods registered for the requested path, available to the method not allowed handler
+#[derive(Debug, Clone)]
+pub struct AllowedMethods {
+    inner: Vec<String>,
+}
+
+impl AllowedMethods {
+    pub(crate) fn new(inner: Vec<String>) -> Self {
+        Self { inner }
+    }
+
+    /// Value for the `Allow` response header
+    pub fn header_value(&self) -> String {
+        self.inner.join(", ")
+    }
+}
+
+impl Deref for AllowedMethods {
+    type Target = Vec<String>;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'a> FromRequest<'a> for AllowedMethods {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(req: &'a http_tokio::Request, payload: &'a http_tokio::BodyReader) -> Self::Future {
+        Box::pin(async move {
+            let allowed = Extension::<'a, AllowedMethods>::from_req(req, payload).await?;
+            Ok(allowed.clone())
+        })
+    }
+}
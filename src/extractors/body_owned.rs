@@ -1,6 +1,6 @@
 use std::ops::{Deref, DerefMut};
 
-use super::FromRequest;
+use super::{de::Entries, FromRequest};
 use crate::{error::HttpError, result::HttpResult};
 use bytes::Bytes;
 use futures::future::BoxFuture;
@@ -11,11 +11,34 @@ pub struct BodyOwned {
     bytes: Vec<u8>,
 }
 
+/// Maximum body size of the body extractors unless the router or a scope sets one with `max_body_size`
+pub const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Maximum body size of the matched route, inserted by the router when one is configured
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BodyLimit(pub(crate) usize);
+
+impl BodyLimit {
+    pub(crate) async fn of(req: &Request) -> usize {
+        req.extensions.get::<BodyLimit>().await.map_or(DEFAULT_MAX_BODY_SIZE, |limit| limit.0)
+    }
+
+    pub(crate) fn exceeded() -> HttpError {
+        HttpError::new("payload too large", 413)
+    }
+}
+
 impl BodyOwned {
     pub fn json<T: DeserializeOwned>(self) -> HttpResult<T> {
         serde_json::from_str::<T>(&self.text()?).map_err(|e| HttpError::new(e.to_string(), 400))
     }
 
+    pub fn form<T: DeserializeOwned>(self) -> HttpResult<T> {
+        let pairs: Vec<(String, String)> = form_urlencoded::parse(&self.bytes).into_owned().collect();
+        let entries = Entries::new("form", pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        T::deserialize(entries).map_err(|e| HttpError::new(e.0, 400))
+    }
+
     pub fn text(self) -> HttpResult<String> {
         String::from_utf8(self.bytes).map_err(|e| HttpError::new(e.to_string(), 400))
     }
@@ -27,12 +50,9 @@ impl BodyOwned {
 
 impl<'a> FromRequest<'a> for BodyOwned {
     type Future = BoxFuture<'a, HttpResult<Self>>;
-    fn from_req(_: &'a Request, payload: &'a BodyReader) -> Self::Future {
+    fn from_req(req: &'a Request, payload: &'a BodyReader) -> Self::Future {
         Box::pin(async move {
-            let bytes = payload
-                .read_all()
-                .await
-                .map_err(|err| HttpError::new(format!("io error reading body: {err}"), 500))?;
+            let bytes = read_limited(req, payload, BodyLimit::of(req).await).await?;
             if bytes.is_empty() {
                 return Err(HttpError::new("found empty body".to_string(), 500));
             }
@@ -41,23 +61,24 @@ impl<'a> FromRequest<'a> for BodyOwned {
     }
 }
 
+/// Extracts an `application/json` body and, as a handler return value, serializes into one
 #[derive(Debug)]
-pub struct Json<T: DeserializeOwned, const ERR_CODE: u16 = 400>(T);
+pub struct Json<T, const ERR_CODE: u16 = 400>(pub T);
 
-impl<T: DeserializeOwned, const ERR_CODE: u16> Json<T, ERR_CODE> {
+impl<T, const ERR_CODE: u16> Json<T, ERR_CODE> {
     pub fn into_inner(self) -> T {
         self.0
     }
 }
 
-impl<T: DeserializeOwned, const ERR_CODE: u16> Deref for Json<T, ERR_CODE> {
+impl<T, const ERR_CODE: u16> Deref for Json<T, ERR_CODE> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl<T: DeserializeOwned, const ERR_CODE: u16> DerefMut for Json<T, ERR_CODE> {
+impl<T, const ERR_CODE: u16> DerefMut for Json<T, ERR_CODE> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
@@ -67,6 +88,7 @@ impl<'a, T: DeserializeOwned, const ERR_CODE: u16> FromRequest<'a> for Json<T, E
     type Future = BoxFuture<'a, HttpResult<Self>>;
     fn from_req(req: &'a Request, payload: &'a BodyReader) -> Self::Future {
         Box::pin(async move {
+            expect_content_type(req, "application/json", |mime| mime == "application/json" || mime.ends_with("+json"))?;
             let t = BodyOwned::from_req(req, payload)
                 .await?
                 .json::<T>()
@@ -75,3 +97,74 @@ impl<'a, T: DeserializeOwned, const ERR_CODE: u16> FromRequest<'a> for Json<T, E
         })
     }
 }
+
+/// Extracts an `application/x-www-form-urlencoded` body, with the same rules as `Query`
+#[derive(Debug)]
+pub struct Form<T: DeserializeOwned, const ERR_CODE: u16 = 400>(T);
+
+impl<T: DeserializeOwned, const ERR_CODE: u16> Form<T, ERR_CODE> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned, const ERR_CODE: u16> Deref for Form<T, ERR_CODE> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned, const ERR_CODE: u16> DerefMut for Form<T, ERR_CODE> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, T: DeserializeOwned, const ERR_CODE: u16> FromRequest<'a> for Form<T, ERR_CODE> {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(req: &'a Request, payload: &'a BodyReader) -> Self::Future {
+        Box::pin(async move {
+            expect_content_type(req, "application/x-www-form-urlencoded", |mime| mime == "application/x-www-form-urlencoded")?;
+            let t = BodyOwned::from_req(req, payload)
+                .await?
+                .form::<T>()
+                .map_err(|e| e.status(ERR_CODE))?;
+            Ok(Form(t))
+        })
+    }
+}
+
+/// Reads the body chunk by chunk, failing with 413 as soon as it grows past `limit`.
+/// A declared `Content-Length` over the limit is rejected before reading anything
+async fn read_limited(req: &Request, payload: &BodyReader, limit: usize) -> HttpResult<Vec<u8>> {
+    let declared = req.headers.get("Content-Length").and_then(|len| len.trim().parse::<usize>().ok());
+    if declared.is_some_and(|len| len > limit) {
+        return Err(BodyLimit::exceeded());
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = payload
+        .read_chunk()
+        .await
+        .map_err(|err| HttpError::new(format!("io error reading body: {err}"), 500))?
+    {
+        if bytes.len() + chunk.len() > limit {
+            return Err(BodyLimit::exceeded());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Rejects with 415 a request whose media type, parameters aside, is not accepted
+fn expect_content_type(req: &Request, expected: &str, accepts: fn(&str) -> bool) -> HttpResult<()> {
+    let mime = req.headers
+        .get("Content-Type")
+        .map(|content_type| content_type.split(';').next().unwrap_or_default().trim().to_lowercase());
+    match mime {
+        Some(mime) if accepts(&mime) => Ok(()),
+        Some(mime) => Err(HttpError::new(format!("unsupported content type {mime:?}, expected {expected:?}"), 415)),
+        None => Err(HttpError::new(format!("missing content type, expected {expected:?}"), 415)),
+    }
+}
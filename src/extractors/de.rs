@@ -0,0 +1,248 @@
+use std::fmt::Display;
+
+use serde::{
+    de::{self, value::StrDeserializer, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor},
+    forward_to_deserialize_any, Deserializer,
+};
+
+/// Deserialization error for string params, the message already names the offending param
+#[derive(Debug)]
+pub(crate) struct DeError(pub(crate) String);
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+/// Named string params (path captures or query pairs) deserialized as a struct or map keyed by name,
+/// a tuple in order, or a single value when there is only one param
+pub(crate) struct Entries<'v> {
+    label: &'static str,
+    entries: Vec<(&'v str, Vec<&'v str>)>,
+}
+
+impl<'v> Entries<'v> {
+    /// Groups repeated names together, keeping the order in which names first appear
+    pub(crate) fn new(label: &'static str, pairs: impl IntoIterator<Item = (&'v str, &'v str)>) -> Self {
+        let mut entries: Vec<(&'v str, Vec<&'v str>)> = Vec::new();
+        for (name, value) in pairs {
+            match entries.iter_mut().find(|(existing, _)| *existing == name) {
+                Some((_, values)) => values.push(value),
+                None => entries.push((name, vec![value])),
+            }
+        }
+        Entries { label, entries }
+    }
+
+    fn single(self) -> Result<Value<'v>, DeError> {
+        let len = self.entries.len();
+        match self.entries.into_iter().next() {
+            Some((name, values)) if len == 1 => Ok(Value { label: self.label, name, values }),
+            _ => Err(DeError(format!("expected a single {} parameter but found {len}", self.label))),
+        }
+    }
+
+    fn access(self) -> EntriesAccess<'v> {
+        EntriesAccess { label: self.label, entries: self.entries.into_iter(), current: None }
+    }
+}
+
+macro_rules! single_entry {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, 'v> Deserializer<'de> for Entries<'v> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(self.access())
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(self.access())
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.entries.len() != len {
+            return Err(DeError(format!("expected {len} {} parameters but found {}", self.label, self.entries.len())));
+        }
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _: &'static str, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    single_entry! {
+        deserialize_bool deserialize_char deserialize_str deserialize_string
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_option
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct identifier ignored_any
+    }
+}
+
+struct EntriesAccess<'v> {
+    label: &'static str,
+    entries: std::vec::IntoIter<(&'v str, Vec<&'v str>)>,
+    current: Option<(&'v str, Vec<&'v str>)>,
+}
+
+impl<'de, 'v> MapAccess<'de> for EntriesAccess<'v> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((name, values)) => {
+                self.current = Some((name, values));
+                let key: StrDeserializer<'_, DeError> = name.into_deserializer();
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (name, values) = self.current.take().ok_or_else(|| DeError(format!("{} parameter value requested before its name", self.label)))?;
+        seed.deserialize(Value { label: self.label, name, values })
+    }
+}
+
+impl<'de, 'v> SeqAccess<'de> for EntriesAccess<'v> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        self.entries
+            .next()
+            .map(|(name, values)| seed.deserialize(Value { label: self.label, name, values }))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializes the values of a single param, parsing them into the requested primitive.
+/// Sequences take every value of a repeated param, anything else takes the last one
+struct Value<'v> {
+    label: &'static str,
+    name: &'v str,
+    values: Vec<&'v str>,
+}
+
+impl<'v> Value<'v> {
+    fn last(&self) -> &'v str {
+        self.values.last().copied().unwrap_or_default()
+    }
+
+    fn invalid(&self, msg: impl Display) -> DeError {
+        DeError(format!("invalid {} parameter {:?}: {msg}", self.label, self.name))
+    }
+}
+
+macro_rules! parse_value {
+    ($(($method:ident, $visit:ident, $ty:ty))*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.last().parse::<$ty>() {
+                    Ok(val) => visitor.$visit(val),
+                    Err(_) => Err(self.invalid(format!("expected {} but got {:?}", stringify!($ty), self.last()))),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, 'v> Deserializer<'de> for Value<'v> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.values.len() {
+            1 => visitor.visit_str(self.last()),
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.last())
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.last())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let (label, name) = (self.label, self.name);
+        let values = self.values.into_iter().map(|value| (name, vec![value])).collect::<Vec<_>>().into_iter();
+        visitor.visit_seq(EntriesAccess { label, entries: values, current: None })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        let value: StrDeserializer<'_, DeError> = self.last().into_deserializer();
+        visitor.visit_enum(value).map_err(|e| self.invalid(e))
+    }
+
+    parse_value! {
+        (deserialize_bool, visit_bool, bool)
+        (deserialize_char, visit_char, char)
+        (deserialize_i8, visit_i8, i8)
+        (deserialize_i16, visit_i16, i16)
+        (deserialize_i32, visit_i32, i32)
+        (deserialize_i64, visit_i64, i64)
+        (deserialize_i128, visit_i128, i128)
+        (deserialize_u8, visit_u8, u8)
+        (deserialize_u16, visit_u16, u16)
+        (deserialize_u32, visit_u32, u32)
+        (deserialize_u64, visit_u64, u64)
+        (deserialize_u128, visit_u128, u128)
+        (deserialize_f32, visit_f32, f32)
+        (deserialize_f64, visit_f64, f64)
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,56 @@
+use futures::future::BoxFuture;
+use http_tokio::extensions::Extension;
+use crate::{extractors::FromRequest, result::HttpResult};
+
+/// The route that matched the request, available to handlers and middlewares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedRoute {
+    pattern: String,
+    method: Option<String>,
+    name: Option<String>,
+    scopes: Vec<RouteScope>,
+}
+
+/// A scope enclosing the matched route, as registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteScope {
+    pub pattern: String,
+    pub name: Option<String>,
+}
+
+impl MatchedRoute {
+    pub(crate) fn new(pattern: String, method: Option<String>, name: Option<String>, scopes: Vec<RouteScope>) -> Self {
+        Self { pattern, method, name, scopes }
+    }
+
+    /// Full template path of the route, like `/users/{id}`
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Method the route was registered for, `None` when it accepts any method
+    pub fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    /// Name given with `Node::name` to the scope the handler was added to
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Scopes enclosing the route, outermost first. The node the handler was added to is the route itself,
+    /// not one of them, and unnamed `/` scopes without a method are left out
+    pub fn scopes(&self) -> &[RouteScope] {
+        &self.scopes
+    }
+}
+
+impl<'a> FromRequest<'a> for MatchedRoute {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(req: &'a http_tokio::Request, payload: &'a http_tokio::BodyReader) -> Self::Future {
+        Box::pin(async move {
+            let matched = Extension::<'a, MatchedRoute>::from_req(req, payload).await?;
+            Ok(matched.clone())
+        })
+    }
+}
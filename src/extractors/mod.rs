@@ -1,8 +1,24 @@
 mod body_owned;
 mod from_request;
 mod request_params;
+mod allowed_methods;
+mod matched_route;
+pub(crate) mod url_for;
+mod path;
+mod query;
+mod de;
+mod multipart;
+pub(crate) mod state;
 pub mod ext;
 
 pub use from_request::FromRequest;
 pub use request_params::RequestParams;
-pub use body_owned::{BodyOwned, Json};
\ No newline at end of file
+pub use allowed_methods::AllowedMethods;
+pub use matched_route::{MatchedRoute, RouteScope};
+pub use url_for::UrlFor;
+pub use path::Path;
+pub use query::Query;
+pub use multipart::{Multipart, Field};
+pub use state::State;
+pub use body_owned::{BodyOwned, Json, Form, DEFAULT_MAX_BODY_SIZE};
+pub(crate) use body_owned::BodyLimit;
\ No newline at end of file
@@ -0,0 +1,269 @@
+use super::{BodyLimit, FromRequest};
+use crate::{error::HttpError, result::HttpResult};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request};
+
+const MAX_HEADERS_SIZE: usize = 8 * 1024;
+
+/// Streaming reader for `multipart/form-data` bodies.
+///
+/// Parts are read from the `BodyReader` as they arrive, so files never need to be buffered whole:
+/// ```ignore
+/// while let Some(mut field) = multipart.next_field().await? {
+///     while let Some(chunk) = field.chunk().await? { /* ... */ }
+/// }
+/// ```
+pub struct Multipart<'a> {
+    payload: &'a BodyReader,
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+    state: State,
+    part_size: usize,
+    max_part_size: Option<usize>,
+    body_size: usize,
+    body_limit: usize,
+}
+
+#[derive(PartialEq)]
+enum State {
+    Part,     // reading a part body (or the preamble before the first boundary)
+    Boundary, // right after a boundary, before the part headers or the final `--`
+    Done,
+}
+
+impl<'a> Multipart<'a> {
+    fn new(payload: &'a BodyReader, boundary: &str, body_limit: usize) -> Self {
+        Multipart {
+            payload,
+            delimiter: format!("\r\n--{boundary}").into_bytes(),
+            buffer: b"\r\n".to_vec(), // so that a boundary at the very start matches the delimiter
+            state: State::Part,
+            part_size: 0,
+            max_part_size: None,
+            body_size: 0,
+            body_limit,
+        }
+    }
+
+    /// Parts bigger than `limit` bytes fail with 413
+    pub fn max_part_size(mut self, limit: usize) -> Self {
+        self.max_part_size = Some(limit);
+        self
+    }
+
+    /// Moves to the next part, skipping whatever is left of the current one
+    pub async fn next_field(&mut self) -> HttpResult<Option<Field<'_, 'a>>> {
+        while self.state == State::Part {
+            self.part_chunk().await?;
+        }
+        if self.state == State::Done {
+            return Ok(None);
+        }
+
+        while self.buffer.len() < 2 {
+            self.fill().await?;
+        }
+        if self.buffer.starts_with(b"--") {
+            self.state = State::Done;
+            return Ok(None);
+        }
+        if !self.buffer.starts_with(b"\r\n") {
+            return Err(HttpError::new("malformed multipart boundary", 400));
+        }
+        self.buffer.drain(..2);
+
+        let headers_end = loop {
+            if self.buffer.starts_with(b"\r\n") {
+                break 0;
+            }
+            if let Some(pos) = find(&self.buffer, b"\r\n\r\n") {
+                break pos + 2;
+            }
+            if self.buffer.len() > MAX_HEADERS_SIZE {
+                return Err(HttpError::new("multipart part headers too large", 431));
+            }
+            self.fill().await?;
+        };
+
+        let raw_headers = String::from_utf8_lossy(&self.buffer[..headers_end]).into_owned();
+        self.buffer.drain(..headers_end + 2);
+        self.state = State::Part;
+        self.part_size = 0;
+
+        let headers = raw_headers
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Ok(Some(Field::new(self, headers)))
+    }
+
+    async fn part_chunk(&mut self) -> HttpResult<Option<Bytes>> {
+        if self.state != State::Part {
+            return Ok(None);
+        }
+
+        loop {
+            if let Some(pos) = find(&self.buffer, &self.delimiter) {
+                let data: Vec<u8> = self.buffer.drain(..pos).collect();
+                self.buffer.drain(..self.delimiter.len());
+                self.state = State::Boundary;
+                return self.checked(data);
+            }
+
+            // keep enough bytes to recognize a delimiter split across two reads
+            let safe = self.buffer.len().saturating_sub(self.delimiter.len() - 1);
+            if safe > 0 {
+                let data: Vec<u8> = self.buffer.drain(..safe).collect();
+                return self.checked(data);
+            }
+
+            self.fill().await?;
+        }
+    }
+
+    fn checked(&mut self, data: Vec<u8>) -> HttpResult<Option<Bytes>> {
+        self.part_size += data.len();
+        if self.max_part_size.is_some_and(|limit| self.part_size > limit) {
+            return Err(HttpError::new("multipart part too large", 413));
+        }
+        Ok((!data.is_empty()).then(|| data.into()))
+    }
+
+    async fn fill(&mut self) -> HttpResult<()> {
+        let chunk = self.payload
+            .read_chunk()
+            .await
+            .map_err(|err| HttpError::new(format!("io error reading body: {err}"), 500))?;
+        let chunk = chunk.ok_or(HttpError::new("unexpected end of multipart body", 400))?;
+        self.body_size += chunk.len();
+        if self.body_size > self.body_limit {
+            return Err(BodyLimit::exceeded());
+        }
+        self.buffer.extend_from_slice(&chunk);
+        Ok(())
+    }
+}
+
+impl<'a> FromRequest<'a> for Multipart<'a> {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(req: &'a Request, payload: &'a BodyReader) -> Self::Future {
+        Box::pin(async move {
+            let boundary = req.headers
+                .get("Content-Type")
+                .filter(|content_type| content_type.trim_start().to_lowercase().starts_with("multipart/"))
+                .and_then(|content_type| param(content_type, "boundary"))
+                .ok_or(HttpError::new("expected a multipart body with a boundary", 400))?;
+            let body_limit = BodyLimit::of(req).await;
+            let declared = req.headers.get("Content-Length").and_then(|len| len.trim().parse::<usize>().ok());
+            if declared.is_some_and(|len| len > body_limit) {
+                return Err(BodyLimit::exceeded());
+            }
+            Ok(Multipart::new(payload, &boundary, body_limit))
+        })
+    }
+}
+
+/// A single part of a multipart body, its content is streamed with `chunk`
+pub struct Field<'m, 'a> {
+    multipart: &'m mut Multipart<'a>,
+    headers: Vec<(String, String)>,
+    name: Option<String>,
+    file_name: Option<String>,
+}
+
+impl<'m, 'a> Field<'m, 'a> {
+    fn new(multipart: &'m mut Multipart<'a>, headers: Vec<(String, String)>) -> Self {
+        let disposition = headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Disposition"))
+            .map(|(_, value)| value.as_str());
+        let name = disposition.and_then(|value| param(value, "name"));
+        let file_name = disposition.and_then(|value| param(value, "filename"));
+        Field { multipart, headers, name, file_name }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("Content-Type")
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Next piece of the part content, `None` once the part is over
+    pub async fn chunk(&mut self) -> HttpResult<Option<Bytes>> {
+        self.multipart.part_chunk().await
+    }
+
+    /// Buffers the rest of the part, prefer `chunk` for files
+    pub async fn bytes(mut self) -> HttpResult<Bytes> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes.into())
+    }
+
+    pub async fn text(self) -> HttpResult<String> {
+        String::from_utf8(self.bytes().await?.to_vec()).map_err(|e| HttpError::new(e.to_string(), 400))
+    }
+}
+
+/// Value of a `key=value` parameter in a header like `Content-Type` or `Content-Disposition`,
+/// values can be quoted strings with `\` escapes and contain `;` or `=`
+fn param(header: &str, key: &str) -> Option<String> {
+    let mut rest = header.split_once(';')?.1;
+    while !rest.is_empty() {
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        let name_end = rest.find(['=', ';']).unwrap_or(rest.len());
+        let name = rest[..name_end].trim();
+        let Some(raw) = rest[name_end..].strip_prefix('=') else {
+            rest = &rest[name_end..]; // parameter without value
+            continue;
+        };
+        let (value, tail) = param_value(raw.trim_start());
+        if name.eq_ignore_ascii_case(key) {
+            return Some(value);
+        }
+        rest = tail;
+    }
+    None
+}
+
+/// Quoted or plain value at the start of `raw`, followed by what is left after it
+fn param_value(raw: &str) -> (String, &str) {
+    let Some(quoted) = raw.strip_prefix('"') else {
+        let end = raw.find(';').unwrap_or(raw.len());
+        return (raw[..end].trim().to_string(), &raw[end..]);
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => value.extend(chars.next().map(|(_, escaped)| escaped)),
+            '"' => return (value, &quoted[i + 1..]),
+            c => value.push(c),
+        }
+    }
+    (value, "")
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
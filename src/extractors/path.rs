@@ -0,0 +1,43 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{de::Entries, FromRequest, RequestParams};
+use crate::{error::HttpError, result::HttpResult};
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request};
+use serde::de::DeserializeOwned;
+
+/// Extracts the captured path params into any deserializable type:
+/// a struct or map keyed by param name, a tuple in path order, or a single value when only one param is captured
+#[derive(Debug)]
+pub struct Path<T: DeserializeOwned>(T);
+
+impl<T: DeserializeOwned> Path<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned> Deref for Path<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned> DerefMut for Path<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, T: DeserializeOwned> FromRequest<'a> for Path<T> {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(req: &'a Request, payload: &'a BodyReader) -> Self::Future {
+        Box::pin(async move {
+            let params = RequestParams::from_req(req, payload).await?;
+            let params = Entries::new("path", params.ordered());
+            let t = T::deserialize(params).map_err(|e| HttpError::new(e.0, 400))?;
+            Ok(Path(t))
+        })
+    }
+}
@@ -0,0 +1,44 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{de::Entries, FromRequest};
+use crate::{error::HttpError, result::HttpResult};
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request};
+use serde::de::DeserializeOwned;
+
+/// Extracts the percent-decoded query string into any deserializable type.
+/// Repeated keys can be collected into a `Vec`, missing keys into an `Option`
+#[derive(Debug)]
+pub struct Query<T: DeserializeOwned, const ERR_CODE: u16 = 400>(T);
+
+impl<T: DeserializeOwned, const ERR_CODE: u16> Query<T, ERR_CODE> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned, const ERR_CODE: u16> Deref for Query<T, ERR_CODE> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned, const ERR_CODE: u16> DerefMut for Query<T, ERR_CODE> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, T: DeserializeOwned, const ERR_CODE: u16> FromRequest<'a> for Query<T, ERR_CODE> {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(req: &'a Request, _: &'a BodyReader) -> Self::Future {
+        Box::pin(async move {
+            let query = req.path.split_once('?').map(|(_, query)| query).unwrap_or_default();
+            let pairs: Vec<(String, String)> = form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+            let entries = Entries::new("query", pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+            let t = T::deserialize(entries).map_err(|e| HttpError::new(e.0, ERR_CODE))?;
+            Ok(Query(t))
+        })
+    }
+}
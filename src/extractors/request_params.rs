@@ -6,11 +6,17 @@ use crate::{error::HttpError, extractors::FromRequest, result::HttpResult};
 #[derive(Debug, Clone)]
 pub struct RequestParams {
     inner: HashMap<String, String>,
+    order: Vec<String>,
 }
 
 impl RequestParams {
-    pub(crate) fn new(inner: HashMap<String, String>) -> Self {
-        Self { inner }
+    pub(crate) fn new(inner: HashMap<String, String>, order: Vec<String>) -> Self {
+        Self { inner, order }
+    }
+
+    /// Params in the order they appear in the matched pattern
+    pub fn ordered(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.order.iter().filter_map(|key| self.inner.get_key_value(key)).map(|(k, v)| (k.as_str(), v.as_str()))
     }
 
     pub fn param(&self, key: &str) -> HttpResult<String> {
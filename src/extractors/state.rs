@@ -0,0 +1,71 @@
+use std::{any::{type_name, Any, TypeId}, collections::HashMap, future::Future, ops::Deref, sync::Arc};
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request};
+use crate::{error::HttpError, extractors::FromRequest, result::HttpResult};
+
+/// App-wide values registered with `with_state`, keyed by type
+pub(crate) type States = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+tokio::task_local! {
+    // states of the matched route, set for the duration of its middlewares and handler
+    static STATES: Arc<States>;
+}
+
+/// Runs `fut` with the states of the matched route available to the `State` extractor
+pub(crate) async fn scope<F: Future>(states: Arc<States>, fut: F) -> F::Output {
+    STATES.scope(states, fut).await
+}
+
+/// Merges the states of a nested scope over the ones of its parent, nested values win
+pub(crate) fn merge(parent: &Arc<States>, nested: &States) -> Arc<States> {
+    match (parent.is_empty(), nested.is_empty()) {
+        (_, true) => parent.clone(),
+        (true, false) => Arc::new(nested.clone()),
+        (false, false) => {
+            let mut merged = States::clone(parent);
+            merged.extend(nested.iter().map(|(id, state)| (*id, state.clone())));
+            Arc::new(merged)
+        }
+    }
+}
+
+/// Shared state registered on the `Router` or on a scope with `with_state`.
+///
+/// Values are kept behind an `Arc` and resolved with the route, so extracting one is a cheap clone
+/// that never locks the request extensions
+#[derive(Debug)]
+pub struct State<T: Send + Sync + 'static>(Arc<T>);
+
+impl<T: Send + Sync + 'static> State<T> {
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T: Send + Sync + 'static> Clone for State<T> {
+    fn clone(&self) -> Self {
+        State(self.0.clone())
+    }
+}
+
+impl<T: Send + Sync + 'static> Deref for State<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, T: Send + Sync + 'static> FromRequest<'a> for State<T> {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(_: &'a Request, _: &'a BodyReader) -> Self::Future {
+        Box::pin(async move {
+            let state = STATES
+                .try_with(|states| states.get(&TypeId::of::<T>()).cloned())
+                .ok()
+                .flatten()
+                .and_then(|state| state.downcast::<T>().ok())
+                .ok_or(HttpError::new(format!("Trying to obtain unregistered state {}", type_name::<T>()), 500))?;
+            Ok(State(state))
+        })
+    }
+}
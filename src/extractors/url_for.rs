@@ -0,0 +1,103 @@
+use std::{collections::HashMap, sync::Arc};
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request};
+use serde::Serialize;
+use serde_json::Value;
+use crate::{error::{HttpError, UrlError}, extractors::FromRequest, pattern::{chunk_parts, Constraints, Part}, result::HttpResult};
+
+/// Routes named with `Node::name`
+#[derive(Debug, Default)]
+pub(crate) struct RouteNames(pub(crate) HashMap<String, NamedRoute>);
+
+/// Path chunks of a named route, with the constraints its params must satisfy
+#[derive(Debug)]
+pub(crate) struct NamedRoute {
+    pub(crate) chunks: Vec<String>,
+    pub(crate) constraints: Constraints,
+}
+
+impl RouteNames {
+    /// Fills the `{param}` and `*` segments of the named route from a map or a serializable struct,
+    /// the wildcard is taken from the `*` key. Values must satisfy the constraints of their params
+    pub(crate) fn url_for(&self, name: &str, params: &impl Serialize) -> Result<String, UrlError> {
+        let route = self.0.get(name).ok_or_else(|| UrlError::UnknownRoute(name.to_string()))?;
+        let params = match serde_json::to_value(params).map_err(|e| UrlError::InvalidParams(e.to_string()))? {
+            Value::Object(params) => params,
+            Value::Null => Default::default(),
+            _ => return Err(UrlError::InvalidParams("expected a map or a struct".to_string())),
+        };
+        let param = |key: &str| match params.get(key) {
+            Some(Value::String(value)) => Ok(value.clone()),
+            Some(Value::Number(value)) => Ok(value.to_string()),
+            Some(Value::Bool(value)) => Ok(value.to_string()),
+            Some(Value::Null) | None => Err(UrlError::MissingParam(key.to_string())),
+            Some(_) => Err(UrlError::InvalidParams(format!("param {key:?} is not a string, number or bool"))),
+        };
+
+        let mut url = String::new();
+        for chunk in &route.chunks {
+            url.push('/');
+            if chunk == "*" {
+                let rest = param("*")?;
+                let segments: Vec<String> = rest.split('/').map(encode_segment).collect();
+                url.push_str(&segments.join("/"));
+            } else {
+                for part in chunk_parts(chunk) {
+                    match part {
+                        Part::Literal(text) => url.push_str(text),
+                        Part::Capture(name, constraint) => {
+                            let value = param(name)?;
+                            let rejects = |source: &&str| route.constraints.get(*source).is_some_and(|constraint| !constraint.matches(&value));
+                            if let Some(source) = constraint.filter(rejects) {
+                                return Err(UrlError::ConstraintMismatch(name.to_string(), source.to_string()));
+                            }
+                            url.push_str(&encode_segment(&value));
+                        }
+                    }
+                }
+            }
+        }
+        if url.is_empty() {
+            url.push('/');
+        }
+        Ok(url)
+    }
+}
+
+/// Builds the url of a named route from within a handler, see `Router::url_for`
+#[derive(Debug, Clone)]
+pub struct UrlFor(Arc<RouteNames>);
+
+impl UrlFor {
+    /// Named routes of the router serving the request, stored in its extensions
+    pub(crate) fn new(names: Arc<RouteNames>) -> Self {
+        UrlFor(names)
+    }
+
+    pub fn url(&self, name: &str, params: &impl Serialize) -> Result<String, UrlError> {
+        self.0.url_for(name, params)
+    }
+}
+
+impl<'a> FromRequest<'a> for UrlFor {
+    type Future = BoxFuture<'a, HttpResult<Self>>;
+    fn from_req(req: &'a Request, _: &'a BodyReader) -> Self::Future {
+        Box::pin(async move {
+            req.extensions.get::<UrlFor>().await
+                .map(|url_for| url_for.clone())
+                .ok_or(HttpError::new("route names are only available while the router serves a request", 500))
+        })
+    }
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986
+fn encode_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
@@ -1,16 +1,24 @@
 pub mod error;
 pub mod result;
+pub mod response;
 pub mod pattern;
 mod resolver;
 pub mod middleware;
+pub mod metrics;
 pub mod extractors;
 mod router;
 pub mod server;
+#[cfg(feature = "test-util")]
+pub mod test_client;
 
 pub use router::Router;
 pub use resolver::traits::*;
+pub use resolver::routes::{RouteInfo, Routes};
 pub mod node {
     pub use crate::resolver::node::helpers::*;
 }
+pub mod guards {
+    pub use crate::resolver::guards::*;
+}
 
 pub use http_tokio_router_macro::route;
\ No newline at end of file
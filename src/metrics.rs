@@ -0,0 +1,175 @@
+use std::{collections::BTreeMap, fmt::Write, sync::{Arc, Mutex}, time::Instant};
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request, Response};
+use crate::{extractors::MatchedRoute, middleware::{Middleware, Next}, result::HandlerResult, Handler};
+
+const DEFAULT_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+const KNOWN_METHODS: [&str; 9] = ["GET", "HEAD", "POST", "PUT", "DELETE", "CONNECT", "OPTIONS", "TRACE", "PATCH"];
+
+/// Records request counts, in-flight requests and latencies labelled by method, route pattern and status.
+/// Wrapping the router, requests no route matched, answered with 404, 405 or the automatic `OPTIONS` response,
+/// are labelled with the `unmatched` pattern. Methods outside the standard ones are labelled `OTHER`.
+///
+/// Wrap the router with it and mount a clone as the handler rendering the Prometheus text format:
+/// ```ignore
+/// let metrics = Metrics::new();
+/// let router = Router::new().wrap(metrics.clone()).at("/metrics", get(metrics));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    inner: Arc<Registry>,
+}
+
+#[derive(Debug)]
+struct Registry {
+    buckets: Vec<f64>,
+    data: Mutex<Data>,
+}
+
+#[derive(Debug, Default)]
+struct Data {
+    requests: BTreeMap<(String, String, u16), Series>,
+    in_flight: BTreeMap<(String, String), i64>,
+}
+
+#[derive(Debug)]
+struct Series {
+    count: u64,
+    sum: f64,
+    buckets: Vec<u64>, // cumulative counts, one per upper bound
+}
+
+/// Counts a request as in flight until dropped, so that cancelled requests are not left behind
+struct InFlight {
+    registry: Arc<Registry>,
+    key: (String, String),
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::with_buckets(DEFAULT_BUCKETS.to_vec())
+    }
+
+    /// Upper bounds, in seconds, of the latency histogram buckets
+    pub fn with_buckets(mut buckets: Vec<f64>) -> Self {
+        buckets.sort_by(f64::total_cmp);
+        let registry = Registry { buckets, data: Mutex::new(Data::default()) };
+        Metrics { inner: Arc::new(registry) }
+    }
+
+    /// Metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let data = self.inner.data.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_total Total number of HTTP requests.\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for ((method, pattern, status), series) in &data.requests {
+            let _ = writeln!(out, "http_requests_total{{{}}} {}", labels(method, pattern, Some(*status)), series.count);
+        }
+
+        out.push_str("# HELP http_requests_in_flight Number of HTTP requests being served.\n");
+        out.push_str("# TYPE http_requests_in_flight gauge\n");
+        for ((method, pattern), count) in &data.in_flight {
+            let _ = writeln!(out, "http_requests_in_flight{{{}}} {}", labels(method, pattern, None), count);
+        }
+
+        out.push_str("# HELP http_request_duration_seconds HTTP request latencies in seconds.\n");
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for ((method, pattern, status), series) in &data.requests {
+            let labels = labels(method, pattern, Some(*status));
+            for (bound, count) in self.inner.buckets.iter().zip(&series.buckets) {
+                let _ = writeln!(out, "http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {count}");
+            }
+            let _ = writeln!(out, "http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}", series.count);
+            let _ = writeln!(out, "http_request_duration_seconds_sum{{{labels}}} {}", series.sum);
+            let _ = writeln!(out, "http_request_duration_seconds_count{{{labels}}} {}", series.count);
+        }
+        out
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    fn start(self: &Arc<Self>, method: String, pattern: String) -> InFlight {
+        let key = (method, pattern);
+        *self.data.lock().unwrap().in_flight.entry(key.clone()).or_insert(0) += 1;
+        InFlight { registry: self.clone(), key }
+    }
+
+    fn record(&self, method: String, pattern: String, status: u16, seconds: f64) {
+        let mut data = self.data.lock().unwrap();
+        let series = data.requests.entry((method, pattern, status)).or_insert_with(|| Series {
+            count: 0,
+            sum: 0.0,
+            buckets: vec![0; self.buckets.len()],
+        });
+        series.count += 1;
+        series.sum += seconds;
+        for (bound, count) in self.buckets.iter().zip(series.buckets.iter_mut()) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut data = self.registry.data.lock().unwrap();
+        if let Some(count) = data.in_flight.get_mut(&self.key) {
+            *count -= 1;
+        }
+    }
+}
+
+impl Middleware for Metrics {
+    fn handle<'a>(self: Arc<Self>, req: &'a Request, _: &'a BodyReader, next: Next<'a>) -> BoxFuture<'a, Response> {
+        Box::pin(async move {
+            let pattern = match req.extensions.get::<MatchedRoute>().await {
+                Some(matched) => matched.pattern().to_string(),
+                None => "unmatched".to_string(),
+            };
+            let method = match KNOWN_METHODS.contains(&req.method.as_str()) {
+                true => req.method.clone(),
+                false => "OTHER".to_string(),
+            };
+            let in_flight = self.inner.start(method.clone(), pattern.clone());
+            let started = Instant::now();
+
+            let res = next().await;
+
+            drop(in_flight);
+            let seconds = started.elapsed().as_secs_f64();
+            self.inner.record(method, pattern, res.status.as_u16(), seconds);
+            res
+        })
+    }
+}
+
+impl Handler for Metrics {
+    fn handle<'a>(&self, _: &'a Request, _: &'a BodyReader) -> HandlerResult<'a> {
+        let body = self.render();
+        Box::pin(async move {
+            Ok(Response::build().header(("Content-Type", "text/plain; version=0.0.4")).body(body))
+        })
+    }
+}
+
+fn labels(method: &str, pattern: &str, status: Option<u16>) -> String {
+    let mut labels = format!("method=\"{}\",pattern=\"{}\"", escape(method), escape(pattern));
+    if let Some(status) = status {
+        let _ = write!(labels, ",status=\"{status}\"");
+    }
+    labels
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -6,6 +6,14 @@ pub type Next<'a> = Arc<dyn Fn() -> BoxFuture<'a, Response> + Send + Sync + 'a>;
 
 pub trait Middleware: Send + Sync + 'static + std::fmt::Debug {
     fn handle<'a>(self: Arc<Self>, req: &'a Request, payload: &'a BodyReader, next: Next<'a>) -> BoxFuture<'a, Response>;
+
+    /// Shown by the route table, the type name without its module path by default
+    fn name(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        let path = name.split('<').next().unwrap_or(name);
+        let module = path.rfind("::").map_or(0, |idx| idx + 2);
+        name[module..].to_string()
+    }
 }
 
 pub type MiddlewareStack = Vec<Arc<dyn Middleware>>;
\ No newline at end of file
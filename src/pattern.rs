@@ -1,30 +1,33 @@
 use super::error::PatternError;
-use std::fmt::{Debug, Display};
+use regex::Regex;
+use std::{collections::HashMap, fmt::{Debug, Display}, sync::Arc};
 
-const ALLOWED_CHARS: [char; 5] = ['*', '{', '}', '_', '-'];
+const ALLOWED_CHARS: [char; 6] = ['*', '{', '}', '_', '-', '.'];
+
+/// Also allowed in the literal text of partial segments, such as `@{username}`
+const PARTIAL_CHARS: [char; 1] = ['@'];
 
 #[derive(Clone, Debug)]
 pub struct Pattern {
     pub method: String,      // HTTP method (free-form, uppercase)
     pub full_path: String,   // The full path
     pub chunks: Vec<String>, // Path split into chunks
+    pub(crate) constraints: Arc<Constraints>, // compiled once while parsing
 }
 
 type PatternResult<T> = Result<T, PatternError>;
 
+/// Compiled segment constraints, keyed by their source
+pub(crate) type Constraints = HashMap<String, Constraint>;
+
 impl Pattern {
-    pub fn new(method: impl AsRef<str>, path: impl AsRef<str>) -> Self {
-        let method = method.as_ref().to_string();
-        let full_path = path.as_ref().to_string();
-        let chunks = full_path.split("/").map(|c| c.to_string()).collect();
-        Self { method, full_path, chunks }
-    }
-    
     pub fn parse(input: &str) -> PatternResult<Self> {
         let mut method = "ALL".to_string();
         let path: &str;
 
-        let (method_candidate, maybe_path) = if let Some((m, r)) = input.split_once(':') {
+        // a colon after the first slash belongs to a segment constraint, not to the method
+        let method_split = input.split_once(':').filter(|(m, _)| !m.contains('/'));
+        let (method_candidate, maybe_path) = if let Some((m, r)) = method_split {
             (Some(m), r)
         } else {
             (None, input)
@@ -39,6 +42,7 @@ impl Pattern {
                 method,
                 full_path: "*".to_string(),
                 chunks: vec!["*".to_string()],
+                constraints: Arc::default(),
             });
         }
 
@@ -47,13 +51,15 @@ impl Pattern {
         }
 
         path = &maybe_path[1..]; // skip the initial slash
-        let chunks = parse_path(path)?;
+        let mut constraints = Constraints::new();
+        let chunks = parse_path(path, &mut constraints)?;
         let chunks = chunks.into_iter().map(String::from).collect();
 
         Ok(Self {
             method,
             full_path: format!("/{path}"),
             chunks,
+            constraints: Arc::new(constraints),
         })
     }
 
@@ -68,7 +74,7 @@ impl Display for Pattern {
     }
 }
 
-fn parse_path(pattern: &str) -> PatternResult<Vec<&str>> {
+fn parse_path<'p>(pattern: &'p str, constraints: &mut Constraints) -> PatternResult<Vec<&'p str>> {
     let pattern = pattern.trim_end_matches('/');
 
     if pattern.is_empty() {
@@ -84,22 +90,20 @@ fn parse_path(pattern: &str) -> PatternResult<Vec<&str>> {
             continue; // allow double slashes
         }
 
-        if !is_valid_chunk(chunk) {
-            return Err(PatternError::InvalidChars);
-        }
-
         if has_wildcard {
             return Err(PatternError::WildcardPosition);
         }
 
+        // clients resolve `.` and `..` segments away, such a route could never match
+        if chunk.chars().all(|c| c == '.') {
+            return Err(PatternError::InvalidChars);
+        }
+
         if chunk == "*" {
             has_wildcard = true;
             // p_type = PatternType::Dynamic;
-        } else if chunk.starts_with('{') && chunk.ends_with('}') {
-            let trimmed = &chunk[1..chunk.len() - 1];
-            if trimmed.contains('{') || trimmed.contains('}') {
-                return Err(PatternError::InvalidDynamic);
-            }
+        } else {
+            validate_parts(&chunk_parts(chunk), constraints)?;
         }
 
         collected_chunks.push(chunk);
@@ -108,8 +112,201 @@ fn parse_path(pattern: &str) -> PatternResult<Vec<&str>> {
     Ok(collected_chunks)
 }
 
-fn is_valid_chunk(chunk: &str) -> bool {
+fn is_valid_chunk(chunk: &str, partial: bool) -> bool {
     chunk
         .chars()
-        .all(|c| c.is_alphanumeric() || ALLOWED_CHARS.contains(&c))
-}
\ No newline at end of file
+        .all(|c| c.is_alphanumeric() || ALLOWED_CHARS.contains(&c) || (partial && PARTIAL_CHARS.contains(&c)))
+}
+
+fn validate_parts(parts: &[Part], constraints: &mut Constraints) -> PatternResult<()> {
+    let partial = parts.len() > 1;
+    for (i, part) in parts.iter().enumerate() {
+        match part {
+            Part::Literal(text) if text.contains('{') || text.contains('}') => return Err(PatternError::InvalidDynamic),
+            Part::Literal(text) if !is_valid_chunk(text, partial) => return Err(PatternError::InvalidChars),
+            Part::Literal(_) => {}
+            // nothing would tell where the first capture ends
+            Part::Capture(..) if i > 0 && matches!(parts[i - 1], Part::Capture(..)) => return Err(PatternError::InvalidDynamic),
+            Part::Capture(name, constraint) => {
+                if name.contains('{') || name.contains('}') {
+                    return Err(PatternError::InvalidDynamic);
+                }
+                if !is_valid_chunk(name, false) {
+                    return Err(PatternError::InvalidChars);
+                }
+                if let Some(source) = constraint.filter(|source| !constraints.contains_key(*source)) {
+                    constraints.insert(source.to_string(), Constraint::parse(source)?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Piece of a path chunk: literal text or a `{name}` / `{name:constraint}` capture
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Part<'c> {
+    Literal(&'c str),
+    Capture(&'c str, Option<&'c str>),
+}
+
+/// Splits a chunk such as `{name}.{ext}` into its parts. Braces are balanced so constraints
+/// like `{year:\d{4}}` stay whole, an unclosed capture is left as a literal
+pub(crate) fn chunk_parts(chunk: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut literal_start = 0;
+    let mut capture_start = None;
+    let mut depth = 0;
+
+    for (i, c) in chunk.char_indices() {
+        match (c, capture_start) {
+            ('{', None) => {
+                if literal_start < i {
+                    parts.push(Part::Literal(&chunk[literal_start..i]));
+                }
+                capture_start = Some(i + 1);
+                depth = 1;
+            }
+            ('{', Some(_)) => depth += 1,
+            ('}', Some(start)) => {
+                depth -= 1;
+                if depth == 0 {
+                    let inner = &chunk[start..i];
+                    parts.push(match inner.split_once(':') {
+                        Some((name, constraint)) => Part::Capture(name, Some(constraint)),
+                        None => Part::Capture(inner, None),
+                    });
+                    capture_start = None;
+                    literal_start = i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if literal_start < chunk.len() {
+        parts.push(Part::Literal(&chunk[literal_start..]));
+    }
+    parts
+}
+
+/// Name and constraint source of a `{name}` or `{name:constraint}` chunk, `None` for other chunks
+pub(crate) fn dynamic_param(chunk: &str) -> Option<(&str, Option<&str>)> {
+    match chunk_parts(chunk).as_slice() {
+        [Part::Capture(name, constraint)] => Some((name, *constraint)),
+        _ => None,
+    }
+}
+
+/// Capture names of a chunk mixing literal text and captures, `None` for static, `{name}` and `*` chunks
+pub(crate) fn partial_params(chunk: &str) -> Option<Vec<&str>> {
+    let parts = chunk_parts(chunk);
+    let params: Vec<&str> = parts.iter().filter_map(|part| match part {
+        Part::Capture(name, _) => Some(*name),
+        Part::Literal(_) => None,
+    }).collect();
+    (!params.is_empty() && parts.len() > 1).then_some(params)
+}
+
+/// Matches a chunk mixing literal text and captures, such as `{name}.{ext}`, `v{version}` or `@{username}`.
+/// Captures are matched lazily from the left: `{name}.{ext}` splits `a.tar.gz` into `a` and `tar.gz`
+#[derive(Debug)]
+pub(crate) struct SegmentMatcher {
+    regex: Regex,
+    groups: Vec<usize>, // index of the group of each capture, after the groups of the constraints before it
+}
+
+impl SegmentMatcher {
+    /// `constraints` holds the compiled constraints of the captures
+    pub(crate) fn compile(parts: &[Part], constraints: &Constraints) -> PatternResult<Self> {
+        let mut source = String::from("^");
+        let mut groups = Vec::new();
+        let mut next_group = 1;
+        for part in parts {
+            match part {
+                Part::Literal(text) => source.push_str(&regex::escape(text)),
+                Part::Capture(_, constraint) => {
+                    let constraint = constraint
+                        .map(|source| constraints.get(source).ok_or_else(|| PatternError::InvalidConstraint(source.to_string())))
+                        .transpose()?;
+                    let fragment = constraint.map_or(".+?".to_string(), Constraint::fragment);
+                    source.push_str(&format!("({fragment})"));
+                    groups.push(next_group);
+                    next_group += 1 + constraint.map_or(0, Constraint::groups);
+                }
+            }
+        }
+        source.push('$');
+
+        let regex = Regex::new(&source).map_err(|err| PatternError::InvalidConstraint(err.to_string()))?;
+        Ok(SegmentMatcher { regex, groups })
+    }
+
+    /// Whether `segment` matches, without capturing anything
+    pub(crate) fn is_match(&self, segment: &str) -> bool {
+        self.regex.is_match(segment)
+    }
+
+    /// Captured values in order, `None` when the segment does not match
+    pub(crate) fn captures(&self, segment: &str) -> Option<Vec<String>> {
+        let found = self.regex.captures(segment)?;
+        self.groups.iter().map(|&group| Some(found.get(group)?.as_str().to_string())).collect()
+    }
+}
+
+/// Restricts the segments a `{name:constraint}` chunk matches: `int`, `uuid`,
+/// or any other regex the whole segment must match, such as `{slug:[a-z0-9-]+}`
+#[derive(Clone, Debug)]
+pub(crate) enum Constraint {
+    Int,
+    Uuid,
+    Regex(String, Regex), // source as written, compiled anchored to the whole segment
+}
+
+impl Constraint {
+    pub(crate) fn parse(source: &str) -> PatternResult<Self> {
+        match source {
+            "int" => Ok(Constraint::Int),
+            "uuid" => Ok(Constraint::Uuid),
+            "" => Err(PatternError::InvalidConstraint(source.to_string())),
+            // a constraint always spans the whole value, anchors would also break the regex
+            // of the partial segments it is embedded in
+            regex if regex_syntax::parse(regex).is_ok_and(|hir| hir.properties().look_set().contains_anchor()) => {
+                Err(PatternError::InvalidConstraint(source.to_string()))
+            }
+            regex => Regex::new(&format!("^(?:{regex})$"))
+                .map(|compiled| Constraint::Regex(regex.to_string(), compiled))
+                .map_err(|_| PatternError::InvalidConstraint(source.to_string())),
+        }
+    }
+
+    /// Number of capture groups of the constraint's own regex
+    fn groups(&self) -> usize {
+        match self {
+            Constraint::Regex(_, regex) => regex.captures_len() - 1,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn matches(&self, segment: &str) -> bool {
+        match self {
+            // values the `Path` extractor can deserialize into an i64
+            Constraint::Int => !segment.starts_with('+') && segment.parse::<i64>().is_ok(),
+            Constraint::Uuid => segment.len() == 36 && segment.char_indices().all(|(i, c)| match i {
+                8 | 13 | 18 | 23 => c == '-',
+                _ => c.is_ascii_hexdigit(),
+            }),
+            Constraint::Regex(_, regex) => regex.is_match(segment),
+        }
+    }
+
+    /// Regex matching the constrained value inside a larger segment, the only check made there:
+    /// `int` accepts any run of digits, whether or not it fits an i64
+    fn fragment(&self) -> String {
+        match self {
+            Constraint::Int => "-?[0-9]+".to_string(),
+            Constraint::Uuid => "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}".to_string(),
+            Constraint::Regex(source, _) => format!("(?:{source})"),
+        }
+    }
+}
@@ -1,39 +1,64 @@
-use crate::middleware::MiddlewareStack;
+use crate::{extractors::{state::States, MatchedRoute}, middleware::MiddlewareStack};
 use http_tokio::Request;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ResolveContext<'a> {
     pub req: &'a Request,
+    pub(crate) method: &'a str, // differs from the request one when HEAD falls back to GET
     pub(crate) path_segments: Vec<String>,
     pub(crate) params: HashMap<String, String>,
+    pub(crate) param_order: Vec<String>, // keys of params in the order they appear in the path
     pub(crate) layers: MiddlewareStack,
+    pub(crate) body_limit: Option<usize>, // set by the innermost scope with a maximum body size
+    pub(crate) states: Arc<States>,
+    pub(crate) matched: Option<MatchedRoute>,
 }
 
 impl<'a> ResolveContext<'a> {
     pub fn new(req: &'a Request) -> Self {
-        let path_segments = req.path.split("/").filter(|c| !c.is_empty()).map(|c| c.to_string()).collect();
+        let path = req.path.split_once('?').map_or(req.path.as_str(), |(path, _)| path);
+        let path_segments = path.split("/").filter(|c| !c.is_empty()).map(|c| c.to_string()).collect();
         ResolveContext {
             req,
+            method: &req.method,
             path_segments,
             params: HashMap::new(),
+            param_order: Vec::new(),
             layers: MiddlewareStack::new(),
+            body_limit: None,
+            states: Arc::new(States::new()),
+            matched: None,
         }
     }
 
+    pub(crate) fn with_method(mut self, method: &'a str) -> Self {
+        self.method = method;
+        self
+    }
+
     pub fn add_param(&mut self, key: String, value: String) {
-        self.params.insert(key, value);
+        if self.params.insert(key.clone(), value).is_none() {
+            self.param_order.push(key);
+        }
     }
 
     pub fn nest(&mut self, path_segments: Vec<String>, params: HashMap<String, String>, more_layers: MiddlewareStack) -> ResolveContext<'a> {
         let mut layers = self.layers.clone();
         layers.extend(more_layers);
-        ResolveContext { req: self.req, path_segments, params, layers }
+        let mut param_order: Vec<String> = self.param_order.iter().filter(|key| params.contains_key(*key)).cloned().collect();
+        let added: Vec<String> = params.keys().filter(|key| !param_order.contains(key)).cloned().collect();
+        param_order.extend(added);
+        ResolveContext { req: self.req, method: self.method, path_segments, params, param_order, layers, body_limit: self.body_limit, states: self.states.clone(), matched: self.matched.clone() }
     }
 
     pub fn absorb(&mut self, another: ResolveContext<'a>) {
         self.path_segments = another.path_segments.clone();
         self.params = another.params.clone();
+        self.param_order = another.param_order.clone();
         self.layers = another.layers.clone();
+        self.body_limit = another.body_limit;
+        self.states = another.states.clone();
+        self.matched = another.matched.clone();
     }
 }
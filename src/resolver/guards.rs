@@ -0,0 +1,199 @@
+use http_tokio::Request;
+use crate::{error::PatternError, resolver::{ctx::ResolveContext, host::{request_host, HostPattern}, traits::Guard}};
+
+/// Any `Fn(&Request) -> bool` is a guard
+impl<F: Fn(&Request) -> bool + Send + Sync + 'static> Guard for F {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        self(ctx.req)
+    }
+}
+
+impl Guard for Box<dyn Guard> {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        self.as_ref().check(ctx)
+    }
+
+    fn validate(&self) -> Result<(), PatternError> {
+        self.as_ref().validate()
+    }
+}
+
+pub struct Header {
+    name: String,
+    value: Option<String>,
+}
+
+/// Matches requests with the header `name` set to exactly `value`
+pub fn header(name: &str, value: &str) -> Header {
+    Header { name: name.to_string(), value: Some(value.to_string()) }
+}
+
+/// Matches requests with the header `name`, whatever its value
+pub fn header_exists(name: &str) -> Header {
+    Header { name: name.to_string(), value: None }
+}
+
+impl Guard for Header {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        match (ctx.req.headers.get(&self.name), &self.value) {
+            (Some(found), Some(expected)) => found.trim() == expected,
+            (found, None) => found.is_some(),
+            (None, _) => false,
+        }
+    }
+}
+
+pub struct ContentType {
+    mime: String,
+}
+
+/// Matches requests whose `Content-Type` is `mime`, parameters such as `charset` aside
+pub fn content_type(mime: &str) -> ContentType {
+    ContentType { mime: mime.to_lowercase() }
+}
+
+impl Guard for ContentType {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        ctx.req.headers
+            .get("Content-Type")
+            .and_then(|content_type| content_type.split(';').next())
+            .is_some_and(|mime| mime.trim().eq_ignore_ascii_case(&self.mime))
+    }
+}
+
+pub struct QueryParam {
+    name: String,
+}
+
+/// Matches requests with the query param `name`, even if empty
+pub fn query(name: &str) -> QueryParam {
+    QueryParam { name: name.to_string() }
+}
+
+impl Guard for QueryParam {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        let query = ctx.req.path.split_once('?').map(|(_, query)| query).unwrap_or_default();
+        form_urlencoded::parse(query.as_bytes()).any(|(key, _)| key == self.name)
+    }
+}
+
+pub struct Host {
+    pattern: Result<HostPattern, PatternError>, // invalid patterns are reported when the router is built
+}
+
+/// Matches the `Host` header, port aside and case-insensitively.
+/// A `*` label matches any single label and a `{name}` label captures it as a param,
+/// so `*.example.com` matches `api.example.com` but not `example.com`
+pub fn host(pattern: &str) -> Host {
+    Host { pattern: HostPattern::parse(pattern) }
+}
+
+impl Guard for Host {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        let Ok(pattern) = &self.pattern else {
+            return false;
+        };
+        let captures = request_host(ctx.req).and_then(|host| pattern.matches(host));
+        let Some(captures) = captures else {
+            return false;
+        };
+        for (name, value) in captures {
+            ctx.add_param(name, value);
+        }
+        true
+    }
+
+    fn validate(&self) -> Result<(), PatternError> {
+        self.pattern.as_ref().map(|_| ()).map_err(Clone::clone)
+    }
+}
+
+/// Guards combined by `all` and `any`: tuples of up to 8 guards or a `Vec<Box<dyn Guard>>`
+pub trait GuardSet: Send + Sync + 'static {
+    fn guards(&self) -> Vec<&dyn Guard>;
+}
+
+impl GuardSet for Vec<Box<dyn Guard>> {
+    fn guards(&self) -> Vec<&dyn Guard> {
+        self.iter().map(|guard| guard.as_ref()).collect()
+    }
+}
+
+macro_rules! guard_set_tuples {
+    ($(($($name:ident $idx:tt),+))+) => {
+        $(
+            impl<$($name: Guard),+> GuardSet for ($($name,)+) {
+                fn guards(&self) -> Vec<&dyn Guard> {
+                    vec![$(&self.$idx),+]
+                }
+            }
+        )+
+    };
+}
+
+guard_set_tuples!{
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+}
+
+pub struct All<S: GuardSet>(S);
+pub struct Any<S: GuardSet>(S);
+pub struct Not<G: Guard>(G);
+
+/// Matches when every guard matches
+pub fn all<S: GuardSet>(guards: S) -> All<S> {
+    All(guards)
+}
+
+/// Matches when at least one guard matches, keeping the params captured by the first one only
+pub fn any<S: GuardSet>(guards: S) -> Any<S> {
+    Any(guards)
+}
+
+/// Matches when `guard` does not, its captured params are discarded
+pub fn not<G: Guard>(guard: G) -> Not<G> {
+    Not(guard)
+}
+
+impl<S: GuardSet> Guard for All<S> {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        self.0.guards().into_iter().all(|guard| guard.check(ctx))
+    }
+
+    fn validate(&self) -> Result<(), PatternError> {
+        self.0.guards().into_iter().try_for_each(Guard::validate)
+    }
+}
+
+impl<S: GuardSet> Guard for Any<S> {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        for guard in self.0.guards() {
+            let mut attempt = ctx.clone();
+            if guard.check(&mut attempt) {
+                *ctx = attempt;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn validate(&self) -> Result<(), PatternError> {
+        self.0.guards().into_iter().try_for_each(Guard::validate)
+    }
+}
+
+impl<G: Guard> Guard for Not<G> {
+    fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool {
+        !self.0.check(&mut ctx.clone())
+    }
+
+    fn validate(&self) -> Result<(), PatternError> {
+        self.0.validate()
+    }
+}
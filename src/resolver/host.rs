@@ -0,0 +1,67 @@
+use http_tokio::Request;
+use crate::error::PatternError;
+
+/// Host name template: `*` matches any single label and `{name}` captures it,
+/// so `{tenant}.example.com` matches `acme.example.com` but not `example.com`
+#[derive(Debug, Clone)]
+pub(crate) struct HostPattern {
+    labels: Vec<Label>,
+}
+
+#[derive(Debug, Clone)]
+enum Label {
+    Exact(String),
+    Any,
+    Capture(String),
+}
+
+impl HostPattern {
+    pub(crate) fn parse(pattern: &str) -> Result<Self, PatternError> {
+        let invalid = || PatternError::InvalidHost(pattern.to_string());
+        let labels = pattern.trim_end_matches('.').split('.').map(|label| {
+            if label == "*" {
+                return Ok(Label::Any);
+            }
+            if let Some(name) = label.strip_prefix('{').and_then(|label| label.strip_suffix('}')) {
+                let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+                return valid.then(|| Label::Capture(name.to_string())).ok_or_else(invalid);
+            }
+            let valid = !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            valid.then(|| Label::Exact(label.to_lowercase())).ok_or_else(invalid)
+        });
+        Ok(HostPattern { labels: labels.collect::<Result<_, _>>()? })
+    }
+
+    /// Captured labels when `host` matches, compared case-insensitively
+    pub(crate) fn matches(&self, host: &str) -> Option<Vec<(String, String)>> {
+        let labels: Vec<&str> = host.split('.').collect();
+        if labels.len() != self.labels.len() || labels.iter().any(|label| label.is_empty()) {
+            return None;
+        }
+
+        let mut captures = Vec::new();
+        for (expected, label) in self.labels.iter().zip(labels) {
+            match expected {
+                Label::Exact(expected) if !expected.eq_ignore_ascii_case(label) => return None,
+                Label::Capture(name) => captures.push((name.clone(), label.to_lowercase())),
+                _ => {}
+            }
+        }
+        Some(captures)
+    }
+
+    /// Without `*` nor `{name}` labels
+    pub(crate) fn is_exact(&self) -> bool {
+        self.labels.iter().all(|label| matches!(label, Label::Exact(_)))
+    }
+}
+
+/// `Host` header of the request, without port nor trailing dot
+pub(crate) fn request_host(req: &Request) -> Option<&str> {
+    let host = req.headers.get("Host")?.trim();
+    let host = match host.starts_with('[') {
+        true => host.split_once(']').map_or(host, |(ip, _)| &ip[1..]),
+        false => host.rsplit_once(':').map_or(host, |(host, _)| host),
+    };
+    Some(host.trim_end_matches('.'))
+}
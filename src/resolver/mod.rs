@@ -1,3 +1,7 @@
 pub mod ctx;
 pub mod traits;
-pub mod node;
\ No newline at end of file
+pub mod node;
+pub mod tree;
+pub mod routes;
+pub mod guards;
+pub mod host;
\ No newline at end of file
@@ -1,98 +1,72 @@
-use std::{cmp::Ordering, collections::HashMap, sync::Arc};
-use crate::{middleware::{Middleware, MiddlewareStack}, pattern::Pattern, resolver::{ctx::ResolveContext, traits::{Handler, Resolver}}};
+use std::{any::TypeId, sync::{Arc, OnceLock}};
+use crate::{error::RegisterError, extractors::state::States, middleware::{Middleware, MiddlewareStack}, pattern::Pattern, resolver::{ctx::ResolveContext, traits::{Guard, Handler, Resolver}, tree::RouteTree}};
 
 pub struct Node {
-    // guards: Vec<Box<dyn Guard>>,
-    pattern: Pattern,
-    layers: MiddlewareStack,
-    childs: Vec<Box<dyn Resolver>>,
+    pub(crate) guards: Vec<Arc<dyn Guard>>,
+    pub(crate) pattern: Result<Pattern, RegisterError>, // invalid patterns are reported when the router is built
+    pub(crate) layers: MiddlewareStack,
+    pub(crate) childs: Vec<Arc<dyn Resolver>>,
+    pub(crate) body_limit: Option<usize>,
+    pub(crate) states: States,
+    pub(crate) name: Option<String>,
+    tree: OnceLock<RouteTree>,
 }
 
 impl Resolver for Node {
     fn resolve<'a, 'ctx>(&'ctx self, ctx: &'a mut ResolveContext<'ctx>) -> Option<&'ctx dyn Handler> {
-        if &ctx.req.method != &self.pattern.method && self.pattern.method != "ALL" {
-            return None;
-        }
+        self.compiled().resolve(ctx)
+    }
 
-        match self.check_path(ctx) {
-            Some((updated_params, updated_segments)) => {
-                let mut nested_ctx = ctx.nest(updated_segments, updated_params, self.layers.clone());
-                match self.childs.iter().find_map(|node| node.resolve(&mut nested_ctx)) {
-                    Some(child) => {
-                        ctx.absorb(nested_ctx);
-                        Some(child)
-                    },
-                    None => None,
-                }
-            },
-            None => None,
-        }
+    fn as_node(&self) -> Option<&Node> {
+        Some(self)
     }
 }
 
 impl Node {
-    fn check_path<'a, 'ctx>(&'ctx self, ctx: &'a mut ResolveContext<'ctx>) -> Option<(HashMap<String, String>, Vec<String>)> {
-        let mut segments = ctx.path_segments.clone();
-        let mut params = HashMap::<String, String>::new();
-
-        if self.pattern.chunks.len() == 0 { return Some((params, segments)) }
-
-
-        for (i, guard_segment) in self.pattern.chunks.iter().enumerate() {
-            if guard_segment == "*" {
-                params.insert("*".to_string(), segments.join("/"));
-                segments = Vec::with_capacity(0);
-                break;
-            }
-
-            let (head, tail) = match segments.split_first() {
-                Some(pair) => pair,
-                None => match i.cmp(&self.pattern.chunks.len()) {
-                    Ordering::Greater => break,
-                    _ => return None,
-                }
-            };
-
-            if guard_segment.starts_with("{") && guard_segment.ends_with("}") {
-                let param = guard_segment[1..guard_segment.len() - 1].to_string();
-                params.insert(param, head.to_string());
-                segments = tail.to_vec();
-                continue;
-            }
-
-            if guard_segment == head {
-                segments = tail.to_vec();  
-                continue;
-            }
-
-            return None;
-        }
-
-        Some((params, segments))
+    /// The route tree is compiled on first use and dropped whenever the node changes
+    pub(crate) fn compiled(&self) -> &RouteTree {
+        self.tree.get_or_init(|| RouteTree::compile(self))
     }
 }
 
-
-
 impl Node {
     pub (crate) fn new() -> Node {
         Node {
+            guards: Vec::new(),
             childs: Vec::new(),
             layers: Vec::new(),
-            pattern: Pattern::parse("ALL:/").unwrap()
+            pattern: Ok(Pattern::parse("ALL:/").unwrap()),
+            body_limit: None,
+            states: States::new(),
+            name: None,
+            tree: OnceLock::new(),
         }
     }
 
     pub (crate) fn with_pattern(pattern: &str) -> Node {
         Node {
+            guards: Vec::new(),
             childs: Vec::new(),
             layers: Vec::new(),
-            pattern: Pattern::parse(pattern).unwrap()
+            pattern: Pattern::parse(pattern).map_err(|err| RegisterError::InvalidPattern(pattern.to_string(), err)),
+            body_limit: None,
+            states: States::new(),
+            name: None,
+            tree: OnceLock::new(),
+        }
+    }
+
+    pub (crate) fn try_with_pattern(pattern: &str) -> Result<Node, RegisterError> {
+        let node = Node::with_pattern(pattern);
+        match node.pattern {
+            Ok(_) => Ok(node),
+            Err(err) => Err(err),
         }
     }
 
     pub fn add(mut self, srv: impl Resolver) -> Self {
-        self.childs.push(Box::new(srv));
+        self.childs.push(Arc::new(srv));
+        self.tree = OnceLock::new();
         self
     }
 
@@ -100,8 +74,43 @@ impl Node {
         self.add(helpers::scope(pattern).add(srv))
     }
 
+    pub fn try_at(self, pattern: &str, srv: impl Resolver) -> Result<Self, RegisterError> {
+        Ok(self.add(Node::try_with_pattern(pattern)?.add(srv)))
+    }
+
+    /// Routes below this node are skipped for the requests `guard` rejects, guards of nested nodes add up
+    pub fn guard(mut self, guard: impl Guard) -> Self {
+        self.guards.push(Arc::new(guard));
+        self.tree = OnceLock::new();
+        self
+    }
+
     pub fn wrap(mut self, middleware: impl Middleware) -> Self {
         self.layers.push(Arc::new(middleware));
+        self.tree = OnceLock::new();
+        self
+    }
+
+    /// Names the routes of the handlers added to this node, for `Router::url_for` and `MatchedRoute::name`
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self.tree = OnceLock::new();
+        self
+    }
+
+    /// Bodies bigger than `limit` bytes are rejected with 413 by the body extractors of this scope,
+    /// nested scopes can set their own limit
+    pub fn max_body_size(mut self, limit: usize) -> Self {
+        self.body_limit = Some(limit);
+        self.tree = OnceLock::new();
+        self
+    }
+
+    /// Shares `state` with the handlers of this scope through the `State<T>` extractor,
+    /// a nested scope registering the same type shadows it
+    pub fn with_state<T: Send + Sync + 'static>(mut self, state: T) -> Self {
+        self.states.insert(TypeId::of::<T>(), Arc::new(state));
+        self.tree = OnceLock::new();
         self
     }
 }
@@ -131,5 +140,7 @@ pub mod helpers {
         (put, "PUT")
         (patch, "PATCH")
         (delete, "DELETE")
+        (head, "HEAD")
+        (options, "OPTIONS")
     }
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+use std::fmt::Display;
+
+/// A route served by the router, as listed by `Router::routes`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    /// `None` when the route accepts any method
+    pub method: Option<String>,
+    /// Full template path, nested scopes included
+    pub pattern: String,
+    /// Names of the middlewares wrapping the route, outermost first
+    pub middlewares: Vec<String>,
+    pub name: Option<String>,
+    /// Custom resolver mounted at `pattern`, its own routes are not known
+    pub opaque: bool,
+    /// Pattern of the virtual host serving the route, `None` for the routes of the router itself
+    pub host: Option<String>,
+}
+
+/// Routes in registration order, virtual hosts last, printed as a table by `Display`
+/// with a `HOST` column when some routes are served by a virtual host
+#[derive(Clone)]
+pub struct Routes {
+    inner: std::vec::IntoIter<RouteInfo>,
+}
+
+impl Routes {
+    pub(crate) fn new(routes: Vec<RouteInfo>) -> Self {
+        Routes { inner: routes.into_iter() }
+    }
+}
+
+impl Iterator for Routes {
+    type Item = RouteInfo;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl Display for Routes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let with_hosts = self.inner.as_slice().iter().any(|route| route.host.is_some());
+        let header = ["HOST", "METHOD", "PATTERN", "NAME", "MIDDLEWARES"].map(String::from);
+        let rows: Vec<[String; 5]> = self.inner.as_slice().iter()
+            .map(|route| [
+                route.host.clone().unwrap_or_default(),
+                route.method.clone().unwrap_or_else(|| "*".to_string()),
+                match route.opaque {
+                    true => format!("{} (custom)", route.pattern),
+                    false => route.pattern.clone(),
+                },
+                route.name.clone().unwrap_or_default(),
+                route.middlewares.join(", "),
+            ])
+            .collect();
+
+        let mut widths = header.clone().map(|title| title.len());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        let skip = match with_hosts {
+            true => 0,
+            false => 1,
+        };
+        for row in std::iter::once(&header).chain(&rows) {
+            let line: Vec<String> = row.iter().zip(widths).skip(skip).map(|(cell, width)| format!("{cell:width$}")).collect();
+            writeln!(f, "{}", line.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,14 @@
 use http_tokio::{BodyReader, Request};
-use crate::{resolver::ctx::ResolveContext, result::HandlerResult};
+use crate::{error::PatternError, resolver::{ctx::ResolveContext, node::Node}, result::HandlerResult};
 
+/// Predicate a request must satisfy to be routed below the node it is registered on, see `Node::guard`
 pub trait Guard: Send + Sync + 'static {
     fn check<'a, 'ctx>(&self, ctx: &'a mut ResolveContext<'ctx>) -> bool;
+
+    /// Invalid guards are reported by `Router::build` and never match
+    fn validate(&self) -> Result<(), PatternError> {
+        Ok(())
+    }
 }
 
 pub trait Handler: Send + Sync + 'static {
@@ -17,6 +23,17 @@ impl<F: for<'a> Fn(&'a Request, &'a BodyReader) -> HandlerResult<'a> + Send + Sy
 
 pub trait Resolver: Send + Sync + 'static {
     fn resolve<'a, 'ctx>(&'ctx self, ctx: &'a mut ResolveContext<'ctx>) -> Option<&'ctx dyn Handler>;
+
+    /// Used by the router to flatten scopes into its route tree.
+    /// Custom resolvers keep the default and are called through `resolve` as an opaque leaf
+    fn as_node(&self) -> Option<&Node> {
+        None
+    }
+
+    /// Used by the router to register plain handlers as leaves of its route tree
+    fn as_handler(&self) -> Option<&dyn Handler> {
+        None
+    }
 }
 
 impl<H: Handler> Resolver for H {
@@ -26,4 +43,8 @@ impl<H: Handler> Resolver for H {
         }
         None
     }
+
+    fn as_handler(&self) -> Option<&dyn Handler> {
+        Some(self)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,470 @@
+use std::{collections::HashMap, sync::Arc};
+use crate::{error::{PatternError, RegisterError}, extractors::{state::{self, States}, url_for::{NamedRoute, RouteNames}, MatchedRoute, RouteScope}, middleware::MiddlewareStack, pattern::{chunk_parts, dynamic_param, partial_params, Constraint, Constraints, Pattern, SegmentMatcher}, resolver::{ctx::ResolveContext, node::Node, routes::RouteInfo, traits::{Guard, Handler, Resolver}}};
+
+/// Prefix tree compiled once from a `Node` hierarchy.
+///
+/// Scopes are flattened into routes hanging from the branch reached by their full path,
+/// so a lookup walks the request segments once instead of scanning every child at every level.
+pub(crate) struct RouteTree {
+    branches: Vec<Branch>,
+    errors: Vec<RegisterError>,
+    names: Arc<RouteNames>,
+    listing: Vec<RouteInfo>,
+}
+
+struct Branch {
+    statics: HashMap<String, usize>,
+    partials: Vec<(String, Arc<SegmentMatcher>, usize)>, // keyed by chunk, such as `{name}.{ext}`
+    dynamics: Vec<Dynamic>, // constrained ones first
+    wildcard: Option<usize>,
+    routes: Vec<Route>,
+}
+
+/// Edge of a `{param}` or `{param:constraint}` segment, keyed by the constraint source
+struct Dynamic {
+    param: String,
+    constraint: Option<(String, Constraint)>,
+    next: usize,
+}
+
+struct Route {
+    method: Option<String>,
+    captures: Vec<(String, Capture)>,
+    guards: Vec<Arc<dyn Guard>>,
+    layers: MiddlewareStack,
+    body_limit: Option<usize>,
+    states: Arc<States>,
+    pattern: String,
+    name: Option<String>,
+    scopes: Vec<RouteScope>,
+    target: Arc<dyn Resolver>,
+}
+
+#[derive(Clone)]
+enum Capture {
+    Segment(usize),
+    Part(usize, Arc<SegmentMatcher>, usize), // nth capture of a partial segment
+    Rest(usize),
+}
+
+#[derive(Clone, Copy)]
+enum Edge<'p> {
+    Static(&'p str),
+    Dynamic(&'p str, Option<(&'p str, &'p Constraint)>),
+    Partial(&'p str, &'p Arc<SegmentMatcher>),
+    Wildcard,
+}
+
+#[derive(Clone)]
+struct Cursor {
+    branch: usize,
+    depth: Option<usize>, // None once a wildcard consumed the rest of the path
+    method: Option<String>,
+    chunks: Vec<String>,
+    captures: Vec<(String, Capture)>,
+    guards: Vec<Arc<dyn Guard>>,
+    layers: MiddlewareStack,
+    body_limit: Option<usize>,
+    states: Arc<States>,
+    scopes: Vec<RouteScope>,
+    constraints: Constraints,
+}
+
+type Found<'ctx> = (&'ctx dyn Handler, ResolveContext<'ctx>);
+
+impl RouteTree {
+    pub(crate) fn compile(root: &Node) -> Self {
+        let mut tree = RouteTree { branches: vec![Branch::new()], errors: Vec::new(), names: Arc::default(), listing: Vec::new() };
+        let cursor = Cursor {
+            branch: 0,
+            depth: Some(0),
+            method: None,
+            chunks: Vec::new(),
+            captures: Vec::new(),
+            guards: Vec::new(),
+            layers: MiddlewareStack::new(),
+            body_limit: None,
+            states: Arc::new(States::new()),
+            scopes: Vec::new(),
+            constraints: Constraints::new(),
+        };
+        tree.insert(root, cursor);
+        tree
+    }
+
+    pub(crate) fn resolve<'ctx>(&'ctx self, ctx: &mut ResolveContext<'ctx>) -> Option<&'ctx dyn Handler> {
+        let (handler, resolved) = self.find(0, 0, ctx)?;
+        ctx.absorb(resolved);
+        Some(handler)
+    }
+
+    /// Methods of the handlers registered for the path of `ctx`, empty when the path itself is unknown.
+    /// HEAD and OPTIONS are always listed for a known path since the router answers them on its own
+    pub(crate) fn allowed_methods(&self, ctx: &ResolveContext) -> Vec<String> {
+        let mut methods = Vec::new();
+        self.collect_methods(0, 0, ctx, &mut methods);
+        if methods.is_empty() {
+            return methods;
+        }
+
+        if methods.iter().any(|method| method == "GET") {
+            methods.push("HEAD".to_string());
+        }
+        methods.push("OPTIONS".to_string());
+        methods.sort();
+        methods.dedup();
+        methods
+    }
+
+    /// Every route in registration order
+    pub(crate) fn routes(&self) -> &[RouteInfo] {
+        &self.listing
+    }
+
+    /// Routes named with `Node::name`, nested custom resolvers aside
+    pub(crate) fn names(&self) -> &Arc<RouteNames> {
+        &self.names
+    }
+
+    /// Problems found while compiling: invalid patterns and guards, duplicated routes and conflicting segments
+    pub(crate) fn errors(&self) -> &[RegisterError] {
+        &self.errors
+    }
+
+    fn insert(&mut self, node: &Node, mut cursor: Cursor) {
+        let pattern = match &node.pattern {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                self.errors.push(err.clone());
+                return;
+            }
+        };
+
+        if pattern.method != "ALL" {
+            if let Some(method) = cursor.method.as_ref().filter(|method| *method != &pattern.method) {
+                let mut nested = cursor.clone();
+                nested.chunks.extend(pattern.chunks.iter().cloned());
+                nested.method = Some(pattern.method.clone());
+                self.errors.push(RegisterError::ConflictingMethod(nested.pattern(), method.clone()));
+                return;
+            }
+            cursor.method = Some(pattern.method.clone());
+        }
+        cursor.constraints.extend(pattern.constraints.iter().map(|(source, constraint)| (source.clone(), constraint.clone())));
+
+        for chunk in &pattern.chunks {
+            cursor.chunks.push(chunk.clone());
+
+            let Some(depth) = cursor.depth else {
+                // no segments are left after a wildcard, nothing nested below it can match
+                let err = match chunk.as_str() {
+                    "*" => RegisterError::DuplicateWildcardSegment,
+                    _ => RegisterError::InvalidPattern(cursor.full_path(), PatternError::WildcardPosition),
+                };
+                self.errors.push(err);
+                return;
+            };
+
+            if chunk == "*" {
+                cursor.branch = self.edge(cursor.branch, Edge::Wildcard);
+                cursor.captures.push(("*".to_string(), Capture::Rest(depth)));
+                cursor.depth = None;
+            } else if let Some((param, source)) = dynamic_param(chunk) {
+                let constraint = source.and_then(|source| Some((source, pattern.constraints.get(source)?)));
+                cursor.branch = self.edge(cursor.branch, Edge::Dynamic(param, constraint));
+                cursor.captures.push((param.to_string(), Capture::Segment(depth)));
+                cursor.depth = Some(depth + 1);
+            } else if let Some(params) = partial_params(chunk) {
+                let matcher = match SegmentMatcher::compile(&chunk_parts(chunk), &pattern.constraints) {
+                    Ok(matcher) => Arc::new(matcher),
+                    Err(err) => {
+                        self.errors.push(RegisterError::InvalidPattern(cursor.full_path(), err));
+                        return;
+                    }
+                };
+                cursor.branch = self.edge(cursor.branch, Edge::Partial(chunk, &matcher));
+                for (i, param) in params.into_iter().enumerate() {
+                    cursor.captures.push((param.to_string(), Capture::Part(depth, matcher.clone(), i)));
+                }
+                cursor.depth = Some(depth + 1);
+            } else {
+                cursor.branch = self.edge(cursor.branch, Edge::Static(chunk));
+                cursor.depth = Some(depth + 1);
+            }
+        }
+
+        for guard in &node.guards {
+            if let Err(err) = guard.validate() {
+                self.errors.push(RegisterError::InvalidGuard(err));
+            }
+        }
+        cursor.guards.extend(node.guards.iter().cloned());
+        cursor.layers.extend(node.layers.iter().cloned());
+        cursor.body_limit = node.body_limit.or(cursor.body_limit);
+        cursor.states = state::merge(&cursor.states, &node.states);
+        // the node a handler is added to is its route, it only encloses the nested nodes
+        let scope = (pattern.method != "ALL" || !pattern.chunks.is_empty() || node.name.is_some())
+            .then(|| RouteScope { pattern: pattern.to_string(), name: node.name.clone() });
+
+        for child in &node.childs {
+            match child.as_node() {
+                Some(nested) => {
+                    let mut cursor = cursor.clone();
+                    cursor.scopes.extend(scope.clone());
+                    self.insert(nested, cursor)
+                }
+                None => self.push_route(&cursor, child.clone(), node.name.clone()),
+            }
+        }
+    }
+
+    fn push_route(&mut self, cursor: &Cursor, target: Arc<dyn Resolver>, name: Option<String>) {
+        if let Some(name) = &name {
+            let names = &mut Arc::get_mut(&mut self.names).expect("route names are not shared while compiling").0;
+            match names.get(name) {
+                Some(named) if named.chunks != cursor.chunks => self.errors.push(RegisterError::DuplicateRouteName(name.clone())),
+                Some(_) => {}
+                None => { names.insert(name.clone(), NamedRoute { chunks: cursor.chunks.clone(), constraints: cursor.constraints.clone() }); }
+            }
+        }
+
+        let routes = &mut self.branches[cursor.branch].routes;
+        // custom resolvers are opaque and guarded handlers may step aside, only plain unguarded handlers can clash
+        let plain = |route_target: &Arc<dyn Resolver>, guards: &[Arc<dyn Guard>]| route_target.as_handler().is_some() && guards.is_empty();
+        let duplicate = plain(&target, &cursor.guards) && routes.iter()
+            .any(|route| route.method == cursor.method && plain(&route.target, &route.guards));
+        if duplicate {
+            self.errors.push(RegisterError::DuplicatePattern(cursor.pattern()));
+        }
+
+        self.listing.push(RouteInfo {
+            method: cursor.method.clone(),
+            pattern: cursor.full_path(),
+            middlewares: cursor.layers.iter().map(|layer| layer.name()).collect(),
+            name: name.clone(),
+            opaque: target.as_handler().is_none(),
+            host: None,
+        });
+
+        let routes = &mut self.branches[cursor.branch].routes;
+        routes.push(Route {
+            method: cursor.method.clone(),
+            captures: cursor.captures.clone(),
+            guards: cursor.guards.clone(),
+            layers: cursor.layers.clone(),
+            body_limit: cursor.body_limit,
+            states: cursor.states.clone(),
+            pattern: cursor.full_path(),
+            name,
+            scopes: cursor.scopes.clone(),
+            target,
+        });
+    }
+
+    fn edge(&mut self, from: usize, edge: Edge) -> usize {
+        let branch = &self.branches[from];
+        let existing = match edge {
+            Edge::Static(chunk) => branch.statics.get(chunk).copied(),
+            Edge::Dynamic(param, constraint) => branch.dynamics.iter()
+                .find(|dynamic| dynamic.constraint.as_ref().map(|(source, _)| source.as_str()) == constraint.map(|(source, _)| source))
+                .map(|dynamic| {
+                    if dynamic.param != param {
+                        self.errors.push(RegisterError::DuplicateDynamicSegment(dynamic.param.clone(), param.to_string()));
+                    }
+                    dynamic.next
+                }),
+            Edge::Partial(chunk, _) => branch.partials.iter().find(|(source, _, _)| source == chunk).map(|(_, _, next)| *next),
+            Edge::Wildcard => branch.wildcard,
+        };
+        if let Some(idx) = existing {
+            return idx;
+        }
+
+        let idx = self.branches.len();
+        let branch = &mut self.branches[from];
+        match edge {
+            Edge::Static(chunk) => { branch.statics.insert(chunk.to_string(), idx); },
+            Edge::Dynamic(param, constraint) => {
+                let dynamic = Dynamic {
+                    param: param.to_string(),
+                    constraint: constraint.map(|(source, parsed)| (source.to_string(), parsed.clone())),
+                    next: idx,
+                };
+                // constrained segments are tried in registration order, before the unconstrained one
+                let position = match constraint {
+                    Some(_) => branch.dynamics.iter().position(|dynamic| dynamic.constraint.is_none()).unwrap_or(branch.dynamics.len()),
+                    None => branch.dynamics.len(),
+                };
+                branch.dynamics.insert(position, dynamic);
+            },
+            Edge::Partial(chunk, matcher) => branch.partials.push((chunk.to_string(), matcher.clone(), idx)),
+            Edge::Wildcard => branch.wildcard = Some(idx),
+        }
+        self.branches.push(Branch::new());
+        idx
+    }
+
+    /// Candidates are tried by specificity regardless of registration order: static segments,
+    /// then partial segments such as `{name}.{ext}`, then `{param:constraint}`, both in registration order,
+    /// then `{param}`, then the routes ending here, then `*`.
+    /// When a subtree yields no handler, or its guards reject the request, the next candidate is tried.
+    fn find<'ctx>(&'ctx self, idx: usize, depth: usize, ctx: &ResolveContext<'ctx>) -> Option<Found<'ctx>> {
+        let branch = &self.branches[idx];
+        let segments = &ctx.path_segments;
+
+        segments.get(depth)
+            .and_then(|segment| branch.statics.get(segment))
+            .and_then(|&next| self.find(next, depth + 1, ctx))
+            .or_else(|| {
+                let segment = segments.get(depth)?;
+                branch.partials.iter()
+                    .filter(|(_, matcher, _)| matcher.is_match(segment))
+                    .find_map(|(_, _, next)| self.find(*next, depth + 1, ctx))
+            })
+            .or_else(|| {
+                let segment = segments.get(depth)?;
+                branch.dynamics.iter()
+                    .filter(|dynamic| dynamic.accepts(segment))
+                    .find_map(|dynamic| self.find(dynamic.next, depth + 1, ctx))
+            })
+            .or_else(|| branch.routes.iter().find_map(|route| route.resolve(ctx, depth)))
+            .or_else(|| self.find(branch.wildcard?, segments.len(), ctx))
+    }
+}
+
+impl RouteTree {
+    /// Routes whose guards reject the request are left out, as if their path was unknown
+    fn collect_methods(&self, idx: usize, depth: usize, ctx: &ResolveContext, methods: &mut Vec<String>) {
+        let branch = &self.branches[idx];
+        let segments = &ctx.path_segments;
+        if depth == segments.len() {
+            let handlers = branch.routes.iter()
+                .filter(|route| route.target.as_handler().is_some() && route.admits(ctx, depth).is_some());
+            methods.extend(handlers.filter_map(|route| route.method.clone()));
+        }
+
+        if let Some(&next) = segments.get(depth).and_then(|segment| branch.statics.get(segment)) {
+            self.collect_methods(next, depth + 1, ctx, methods);
+        }
+        if let Some(segment) = segments.get(depth) {
+            for (_, _, next) in branch.partials.iter().filter(|(_, matcher, _)| matcher.is_match(segment)) {
+                self.collect_methods(*next, depth + 1, ctx, methods);
+            }
+            for dynamic in branch.dynamics.iter().filter(|dynamic| dynamic.accepts(segment)) {
+                self.collect_methods(dynamic.next, depth + 1, ctx, methods);
+            }
+        }
+        if let Some(next) = branch.wildcard {
+            self.collect_methods(next, segments.len(), ctx, methods);
+        }
+    }
+}
+
+impl Cursor {
+    fn full_path(&self) -> String {
+        format!("/{}", self.chunks.join("/"))
+    }
+
+    fn pattern(&self) -> Pattern {
+        Pattern {
+            method: self.method.clone().unwrap_or_else(|| "ALL".to_string()),
+            full_path: self.full_path(),
+            chunks: self.chunks.clone(),
+            constraints: Arc::new(self.constraints.clone()),
+        }
+    }
+}
+
+impl Branch {
+    fn new() -> Self {
+        Branch {
+            statics: HashMap::new(),
+            partials: Vec::new(),
+            dynamics: Vec::new(),
+            wildcard: None,
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl Dynamic {
+    fn accepts(&self, segment: &str) -> bool {
+        self.constraint.as_ref().is_none_or(|(_, constraint)| constraint.matches(segment))
+    }
+}
+
+impl Route {
+    fn resolve<'ctx>(&'ctx self, ctx: &ResolveContext<'ctx>, depth: usize) -> Option<Found<'ctx>> {
+        if self.method.as_ref().is_some_and(|method| method != ctx.method) {
+            return None;
+        }
+
+        let segments = &ctx.path_segments;
+        match self.target.as_handler() {
+            Some(handler) if depth == segments.len() => Some((handler, self.admits(ctx, depth)?)),
+            Some(_) => None,
+            None => {
+                let mut nested = self.admits(ctx, depth)?;
+                let handler = self.target.resolve(&mut nested)?;
+                Some((handler, nested))
+            }
+        }
+    }
+
+    /// Context of the route when its guards accept the request. Guards see the params captured up to the route
+    fn admits<'ctx>(&self, ctx: &ResolveContext<'ctx>, depth: usize) -> Option<ResolveContext<'ctx>> {
+        let mut nested = self.context(ctx, depth);
+        self.guards.iter().all(|guard| guard.check(&mut nested)).then_some(nested)
+    }
+
+    /// Pattern and scopes are prefixed by the ones of the route enclosing a custom resolver
+    fn matched(&self, ctx: &ResolveContext) -> MatchedRoute {
+        let Some(outer) = &ctx.matched else {
+            return MatchedRoute::new(self.pattern.clone(), self.method.clone(), self.name.clone(), self.scopes.clone());
+        };
+        let pattern = match self.pattern.as_str() {
+            "/" => outer.pattern().to_string(),
+            pattern => format!("{}{}", outer.pattern().trim_end_matches('/'), pattern),
+        };
+        let scopes = outer.scopes().iter().chain(&self.scopes).cloned().collect();
+        MatchedRoute::new(pattern, self.method.clone(), self.name.clone(), scopes)
+    }
+
+    fn context<'ctx>(&self, ctx: &ResolveContext<'ctx>, depth: usize) -> ResolveContext<'ctx> {
+        let segments = &ctx.path_segments;
+        let mut params = ctx.params.clone();
+        let mut param_order = ctx.param_order.clone();
+        // the captures of a partial segment are consecutive, its values are matched once for all of them
+        let mut partial: Option<(usize, Vec<String>)> = None;
+        for (name, capture) in &self.captures {
+            let value = match capture {
+                Capture::Segment(i) => segments[*i].clone(),
+                Capture::Part(i, matcher, nth) => {
+                    if partial.as_ref().is_none_or(|(segment, _)| segment != i) {
+                        partial = Some((*i, matcher.captures(&segments[*i]).unwrap_or_default()));
+                    }
+                    partial.as_ref().and_then(|(_, values)| values.get(*nth).cloned()).unwrap_or_default()
+                }
+                Capture::Rest(i) => segments[*i..].join("/"),
+            };
+            if params.insert(name.clone(), value).is_none() {
+                param_order.push(name.clone());
+            }
+        }
+
+        let mut layers = ctx.layers.clone();
+        layers.extend(self.layers.iter().cloned());
+
+        ResolveContext {
+            req: ctx.req,
+            method: ctx.method,
+            path_segments: segments[depth..].to_vec(),
+            params,
+            param_order,
+            layers,
+            body_limit: self.body_limit.or(ctx.body_limit),
+            states: state::merge(&ctx.states, &self.states),
+            matched: Some(self.matched(ctx)),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{error::HttpError, result::{IntoRouteResult, RouteResult}};
+use http_tokio::{content_type::ContentType, Response, StatusCode};
+use serde::Serialize;
+
+pub use crate::extractors::Json;
+
+/// Serializes the value as an `application/json` response body
+impl<T: Serialize, const ERR_CODE: u16> IntoRouteResult for Json<T, ERR_CODE> {
+    fn into(self) -> RouteResult {
+        let body = serde_json::to_vec(&self.0).map_err(HttpError::err)?;
+        Ok(Response::build().content_type(ContentType::Json).body(body))
+    }
+}
+
+/// A `text/html` response body
+#[derive(Debug)]
+pub struct Html<T: Into<String>>(pub T);
+
+impl<T: Into<String>> IntoRouteResult for Html<T> {
+    fn into(self) -> RouteResult {
+        Ok(Response::build().header(("Content-Type", "text/html; charset=utf-8")).body(self.0.into()))
+    }
+}
+
+/// Redirects the client to the `Location` it holds
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    status: StatusCode,
+    location: String,
+}
+
+impl Redirect {
+    /// 307 Temporary Redirect, the method and body are kept
+    pub fn to(location: impl Into<String>) -> Self {
+        Redirect { status: 307.into(), location: location.into() }
+    }
+
+    /// 308 Permanent Redirect, the method and body are kept
+    pub fn permanent(location: impl Into<String>) -> Self {
+        Redirect { status: 308.into(), location: location.into() }
+    }
+
+    /// 303 See Other, the client follows up with a GET
+    pub fn see_other(location: impl Into<String>) -> Self {
+        Redirect { status: 303.into(), location: location.into() }
+    }
+}
+
+impl IntoRouteResult for Redirect {
+    fn into(self) -> RouteResult {
+        Ok(Response::build().status(self.status).header(("Location", self.location)).body(""))
+    }
+}
@@ -1,6 +1,7 @@
 use super::error::HttpError;
+use bytes::Bytes;
 use futures::future::BoxFuture;
-use http_tokio::{content_type::ContentType, Response};
+use http_tokio::{content_type::ContentType, Response, StatusCode};
 
 pub type HttpResult<T> = Result<T, HttpError>;
 pub type RouteResult = HttpResult<Response>;
@@ -44,4 +45,59 @@ impl<T: IntoRouteResult> IntoRouteResult for Result<T, HttpError> {
             Err(e) => Err(e)
         }
     }
-}
\ No newline at end of file
+}
+
+impl IntoRouteResult for String {
+    fn into(self) -> RouteResult {
+        Ok(Response::build().body(self))
+    }
+}
+
+impl IntoRouteResult for Vec<u8> {
+    fn into(self) -> RouteResult {
+        Ok(Response::build().header(("Content-Type", "application/octet-stream")).body(self))
+    }
+}
+
+impl IntoRouteResult for Bytes {
+    fn into(self) -> RouteResult {
+        IntoRouteResult::into(self.to_vec())
+    }
+}
+
+/// No content, answered with 204
+impl IntoRouteResult for () {
+    fn into(self) -> RouteResult {
+        Ok(Response::build().status(StatusCode::NO_CONTENT).body(""))
+    }
+}
+
+/// Overrides the status of the inner response
+impl<S: Into<StatusCode>, T: IntoRouteResult> IntoRouteResult for (S, T) {
+    fn into(self) -> RouteResult {
+        let (status, inner) = self;
+        let mut res = inner.into()?;
+        res.status = status.into();
+        Ok(res)
+    }
+}
+
+/// Overrides the status and adds the headers to the inner response
+impl<S, H, K, V, T> IntoRouteResult for (S, H, T)
+where
+    S: Into<StatusCode>,
+    H: IntoIterator<Item = (K, V)>,
+    K: ToString,
+    V: ToString,
+    T: IntoRouteResult,
+{
+    fn into(self) -> RouteResult {
+        let (status, headers, inner) = self;
+        let mut res = inner.into()?;
+        res.status = status.into();
+        for (name, value) in headers {
+            res.headers.insert(name.to_string(), value.to_string());
+        }
+        Ok(res)
+    }
+}
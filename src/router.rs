@@ -1,7 +1,8 @@
 use std::{future::Future, pin::Pin, sync::Arc};
 use async_fn_traits::AsyncFn2;
 use http_tokio::{BodyReader, Request, Response};
-use crate::{error::HttpError, extractors::RequestParams, middleware::{Middleware, Next}, resolver::{ctx::ResolveContext, node::Node, traits::{Handler, Resolver}}, result::RouteResult};
+use serde::Serialize;
+use crate::{error::{HttpError, RegisterError, UrlError}, extractors::{state, AllowedMethods, BodyLimit, MatchedRoute, RequestParams, UrlFor}, middleware::{Middleware, Next}, resolver::{ctx::ResolveContext, host::{request_host, HostPattern}, node::Node, routes::{RouteInfo, Routes}, traits::{Guard, Resolver}, tree::RouteTree}, result::RouteResult};
 
 pub type NotFoundHandler = Box<
     dyn for<'a> Fn(&'a Request, &'a BodyReader) -> Pin<Box<dyn Future<Output = RouteResult> + Send + Sync + 'a>>
@@ -9,6 +10,8 @@ pub type NotFoundHandler = Box<
         + Sync,
 >;
 
+pub type MethodNotAllowedHandler = NotFoundHandler;
+
 pub type ErrorHandler = Box<
     dyn for<'a> Fn(&'a Request, HttpError) -> Pin<Box<dyn Future<Output = Response> + Send + Sync + 'a>>
         + Send
@@ -17,16 +20,20 @@ pub type ErrorHandler = Box<
 
 pub struct Router {
     root: Node,
+    hosts: Vec<VirtualHost>,
     error_handler: Option<ErrorHandler>,
     not_found_handler: Option<NotFoundHandler>,
+    method_not_allowed_handler: Option<MethodNotAllowedHandler>,
 }
 
 impl Router {
     pub fn new() -> Self {
         Router { 
             root: Node::new(),
+            hosts: Vec::new(),
             error_handler: None,
-            not_found_handler: None
+            not_found_handler: None,
+            method_not_allowed_handler: None,
         }
     }
 
@@ -35,21 +42,61 @@ impl Router {
         self
     }
 
-    // pub fn guard(mut self, guard: impl Guard) -> Self {
-    //     self.root = self.root.guard(guard);
-    //     self
-    // }
+    /// Serves the requests whose `Host` header matches `pattern` with `router`, before any path is resolved.
+    /// Exact hosts are tried before the ones with `*` or `{name}` labels, the latter captured as params.
+    /// Requests for other hosts fall back to the routes of this router.
+    ///
+    /// `router` serves its requests on its own: the guards, states, body limit and handlers set on this router
+    /// don't apply to them, only the middlewares given to `wrap` do, running around the ones of `router`.
+    /// `router` can't have virtual hosts itself, `build` reports them
+    /// ```ignore
+    /// Router::new()
+    ///     .host("api.example.com", api)
+    ///     .host("{tenant}.example.com", tenants)
+    ///     .at("/", get(home));
+    /// ```
+    pub fn host(mut self, pattern: &str, router: Router) -> Self {
+        let parsed = HostPattern::parse(pattern).map_err(|err| RegisterError::InvalidPattern(pattern.to_string(), err));
+        self.hosts.push(VirtualHost { host: pattern.to_string(), pattern: parsed, router });
+        self
+    }
+
+    /// Every request must satisfy `guard` to be routed, others get a 404
+    pub fn guard(mut self, guard: impl Guard) -> Self {
+        self.root = self.root.guard(guard);
+        self
+    }
 
     pub fn at(mut self, pattern: &str, srv: impl Resolver) -> Self {
         self.root = self.root.at(pattern, srv);
         self
     }
 
+    pub fn try_at(mut self, pattern: &str, srv: impl Resolver) -> Result<Self, RegisterError> {
+        self.root = self.root.try_at(pattern, srv)?;
+        Ok(self)
+    }
+
+    /// Runs `middleware` around every request, also the ones no route matched and the ones
+    /// served by virtual hosts. `MatchedRoute` is only available to it when a route matched
     pub fn wrap(mut self, middleware: impl Middleware) -> Self {
         self.root = self.root.wrap(middleware);
         self
     }
 
+    /// Shares `state` with every handler through the `State<T>` extractor
+    pub fn with_state<T: Send + Sync + 'static>(mut self, state: T) -> Self {
+        self.root = self.root.with_state(state);
+        self
+    }
+
+    /// Maximum body size for the body extractors, `DEFAULT_MAX_BODY_SIZE` unless set.
+    /// Scopes can override it with `Node::max_body_size`
+    pub fn max_body_size(mut self, limit: usize) -> Self {
+        self.root = self.root.max_body_size(limit);
+        self
+    }
+
     pub fn set_error_handler<F>(mut self, handler: F) -> Self
     where 
         F: for<'a> AsyncFn2<&'a Request, HttpError, Output = Response> + Send + Sync + 'static,
@@ -68,36 +115,160 @@ impl Router {
         self
     }
 
+    /// Called when the path is registered but not for the request method,
+    /// the registered methods can be extracted with `AllowedMethods` and are sent in the `Allow` header
+    /// unless the handler sets it
+    pub fn set_method_not_allowed_handler<F>(mut self, handler: F) -> Self
+    where 
+        F: for<'a> AsyncFn2<&'a Request, &'a BodyReader, Output = RouteResult> + Send + Sync + 'static, 
+        for<'a> <F as AsyncFn2<&'a Request, &'a BodyReader>>::OutputFuture: Send + Sync 
+    {
+        self.method_not_allowed_handler = Some(Box::new(move |req, payload| Box::pin(handler(req, payload))));
+        self
+    }
+
+    /// Compiles the route table and reports every invalid pattern, duplicated route
+    /// or conflicting segment instead of leaving them unreachable at runtime
+    pub fn build(self) -> Result<Self, Vec<RegisterError>> {
+        let errors = self.errors();
+        match errors.is_empty() {
+            true => Ok(self),
+            false => Err(errors),
+        }
+    }
+
+    /// Every route served, flattened across nested scopes, followed by the routes of the virtual hosts
+    /// labelled with their host pattern. Displays as a table:
+    /// ```ignore
+    /// println!("{}", router.routes());
+    /// ```
+    pub fn routes(&self) -> Routes {
+        let mut routes = self.compiled().routes().to_vec();
+        for vhost in &self.hosts {
+            routes.extend(vhost.router.compiled().routes().iter().map(|route| RouteInfo {
+                host: Some(vhost.host.clone()),
+                ..route.clone()
+            }));
+        }
+        Routes::new(routes)
+    }
+
+    /// Url of the route named `name` with its `{param}` and `*` segments filled from `params`,
+    /// a map or a serializable struct. Values are percent-encoded
+    pub fn url_for(&self, name: &str, params: &impl Serialize) -> Result<String, UrlError> {
+        self.compiled().names().url_for(name, params)
+    }
+
     pub async fn handle_request(&self, req: &Request, payload: &BodyReader) -> Response {
-        let mut resolve_ctx = ResolveContext::new(&req);
-        match self.root.resolve(&mut resolve_ctx) {
+        self.handle_matched(req, payload).await.0
+    }
+}
+
+impl Router {
+    pub(crate) fn compiled(&self) -> &RouteTree {
+        self.root.compiled()
+    }
+
+    pub(crate) fn errors(&self) -> Vec<RegisterError> {
+        let mut errors = self.compiled().errors().to_vec();
+        for vhost in &self.hosts {
+            if let Err(err) = &vhost.pattern {
+                errors.push(err.clone());
+            }
+            if !vhost.router.hosts.is_empty() {
+                errors.push(RegisterError::NestedVirtualHost(vhost.host.clone()));
+            }
+            errors.extend(vhost.router.errors());
+        }
+        errors
+    }
+
+    /// Handles the request, also returning the route that served it
+    pub(crate) async fn handle_matched(&self, req: &Request, payload: &BodyReader) -> (Response, Option<MatchedRoute>) {
+        match self.virtual_host(req) {
+            Some((router, host_params)) => router.dispatch(req, payload, host_params, &self.root.layers).await,
+            None => self.dispatch(req, payload, Vec::new(), &[]).await,
+        }
+    }
+
+    /// Router mounted for the host of the request, with the labels captured from it.
+    /// Virtual hosts of the mounted routers are not looked up, `build` reports them
+    fn virtual_host(&self, req: &Request) -> Option<(&Router, Vec<(String, String)>)> {
+        let host = request_host(req)?;
+        let exact = self.hosts.iter().filter(|vhost| vhost.pattern.as_ref().is_ok_and(HostPattern::is_exact));
+        let others = self.hosts.iter().filter(|vhost| !vhost.pattern.as_ref().is_ok_and(HostPattern::is_exact));
+        exact.chain(others).find_map(|vhost| {
+            let captures = vhost.pattern.as_ref().ok()?.matches(host)?;
+            Some((&vhost.router, captures))
+        })
+    }
+
+    /// Resolves the request and runs it through the middlewares of the matched route, or through
+    /// the middlewares of the router when no route matched, inside the `outer` ones
+    async fn dispatch(&self, req: &Request, payload: &BodyReader, host_params: Vec<(String, String)>, outer: &[Arc<dyn Middleware>]) -> (Response, Option<MatchedRoute>) {
+        let new_ctx = || {
+            let mut ctx = ResolveContext::new(req);
+            for (name, value) in &host_params {
+                ctx.add_param(name.clone(), value.clone());
+            }
+            ctx
+        };
+        let mut resolve_ctx = new_ctx();
+        let mut resolved = self.root.resolve(&mut resolve_ctx);
+
+        // HEAD is served by the GET handler unless a HEAD route is registered
+        let head_fallback = resolved.is_none() && req.method == "HEAD";
+        if head_fallback {
+            resolve_ctx = new_ctx().with_method("GET");
+            resolved = self.root.resolve(&mut resolve_ctx);
+        }
+
+        match resolved {
             Some(handler) => {
-                req.extensions.insert(RequestParams::new(resolve_ctx.params)).await;
-                self.run_stack(&req, &payload, &resolve_ctx.layers, handler).await
+                req.extensions.insert(RequestParams::new(resolve_ctx.params, resolve_ctx.param_order)).await;
+                if let Some(matched) = resolve_ctx.matched.clone() {
+                    req.extensions.insert(matched).await;
+                }
+                if let Some(limit) = resolve_ctx.body_limit {
+                    req.extensions.insert(BodyLimit(limit)).await;
+                }
+                req.extensions.insert(UrlFor::new(self.compiled().names().clone())).await;
+                let endpoint: Next<'_> = Arc::new(|| {
+                    Box::pin(async {
+                        match handler.handle(req, payload).await {
+                            Ok(res) => res,
+                            Err(err) => self.handle_error(req, err).await,
+                        }
+                    })
+                });
+                let stack = self.run_stack(req, payload, outer.iter().chain(&resolve_ctx.layers), endpoint);
+                let res = state::scope(resolve_ctx.states.clone(), stack).await;
+                let res = match head_fallback {
+                    true => strip_body(res),
+                    false => res,
+                };
+                (res, resolve_ctx.matched)
             },
-            None => match self.handle_not_found(req, payload).await {
-                Ok(res) => res,
-                Err(err) => self.handle_error(req, err).await,
+            None => {
+                let allowed = self.compiled().allowed_methods(&resolve_ctx);
+                let endpoint: Next<'_> = Arc::new(move || Box::pin(self.handle_unmatched(req, payload, allowed.clone())));
+                let res = self.run_stack(req, payload, outer.iter().chain(&self.root.layers), endpoint).await;
+                (res, None)
             }
         }
     }
-}
 
-impl Router {
-    async fn run_stack(&self, req: &Request, payload: &BodyReader, middlewares: &[Arc<dyn Middleware + 'static>], handler: &dyn Handler) -> Response {
-        let mut next: Next<'_> = Arc::new(|| {
-            Box::pin(async { 
-                match handler.handle(&req, &payload).await {
-                    Ok(res) => res,
-                    Err(err) => self.handle_error(req, err).await,
-                }
-            })
-        });
-    
-        for mw in middlewares.iter().rev() {
+    /// Runs `endpoint` inside `middlewares`, the first one being the outermost
+    async fn run_stack<'a>(
+        &'a self,
+        req: &'a Request,
+        payload: &'a BodyReader,
+        middlewares: impl DoubleEndedIterator<Item = &'a Arc<dyn Middleware>>,
+        endpoint: Next<'a>,
+    ) -> Response {
+        let mut next = endpoint;
+        for mw in middlewares.rev() {
             let prev = next;
-            let req = &req;
-            let payload = &payload;
             next = Arc::new(move || {
                 mw.clone().handle(req, payload, prev.clone())
             });
@@ -106,6 +277,19 @@ impl Router {
         next().await
     }
 
+    /// Answers a request no route matched with a 404, a 405 or the automatic `OPTIONS` response
+    async fn handle_unmatched(&self, req: &Request, payload: &BodyReader, allowed: Vec<String>) -> Response {
+        let result = match (allowed.is_empty(), req.method == "OPTIONS") {
+            (true, _) => self.handle_not_found(req, payload).await,
+            (false, true) => Ok(Response::build().status(204).header(("Allow", allowed.join(", "))).body("")),
+            (false, false) => self.handle_method_not_allowed(req, payload, AllowedMethods::new(allowed)).await,
+        };
+        match result {
+            Ok(res) => res,
+            Err(err) => self.handle_error(req, err).await,
+        }
+    }
+
     async fn handle_error(&self, req: &Request, err: HttpError) -> Response {
         match &self.error_handler {
             Some(handle_fn) => handle_fn(req, err).await,
@@ -119,10 +303,40 @@ impl Router {
             None => Ok(Response::build().status(404).body("404 Not Found")),
         }
     }
+
+    async fn handle_method_not_allowed(&self, req: &Request, payload: &BodyReader, allowed: AllowedMethods) -> RouteResult {
+        let allow = allowed.header_value();
+        req.extensions.insert(allowed).await;
+        match &self.method_not_allowed_handler {
+            Some(handle_fn) => handle_fn(req, payload).await.map(|mut res| {
+                if res.headers.get("Allow").is_none() {
+                    res.headers.insert("Allow", allow);
+                }
+                res
+            }),
+            None => Ok(Response::build().status(405).header(("Allow", allow)).body("405 Method Not Allowed")),
+        }
+    }
+}
+
+/// Router mounted with `Router::host`
+struct VirtualHost {
+    host: String, // as registered, to label the routes listing
+    pattern: Result<HostPattern, RegisterError>, // invalid patterns are reported when the router is built
+    router: Router,
 }
 
 impl Default for Router {
     fn default() -> Self {
         Self::new()
     }
+}
+
+/// Drops the body of a GET response answering a HEAD request, keeping the length it would have had
+fn strip_body(mut res: Response) -> Response {
+    if res.headers.get("Content-Length").is_none() {
+        res.headers.insert("Content-Length", res.body.len());
+    }
+    res.body.clear();
+    res
 }
\ No newline at end of file
@@ -1,10 +1,39 @@
-use std::{future::Future, pin::Pin};
-use http_tokio::{RequestError, Response, StatusCode};
+use std::{future::Future, net::SocketAddr, pin::Pin, time::Duration};
+use http_tokio::{Request, RequestError, Response, StatusCode};
+use crate::{extractors::MatchedRoute, server::{ListenAddr, RejectReason}};
+
+/// A request served on a connection, as reported to `ServerEvents::on_request_finished`
+#[derive(Debug)]
+pub struct RequestSummary<'a> {
+    pub peer: SocketAddr,
+    pub method: &'a str,
+    pub path: &'a str,
+    /// Matched route, `None` when no route matched
+    pub route: Option<&'a MatchedRoute>,
+    pub status: StatusCode,
+    pub duration: Duration,
+}
 
 pub trait ServerEvents: Send + Sync {
+    fn on_listener_bound(&self, _addr: &ListenAddr) {}
+    fn on_connection_opened(&self, _peer: SocketAddr) {}
+    /// Also called for connections closed by a shutdown, `requests` is how many were served on it
+    fn on_connection_closed(&self, _peer: SocketAddr, _requests: usize) {}
+    fn on_request_started(&self, _peer: SocketAddr, _req: &Request) {}
+    fn on_request_finished(&self, _summary: &RequestSummary<'_>) {}
     fn on_connection_error<'a>(&'a self, err: tokio::io::Error) {
         eprintln!("Connection error: {}", err);
     }
+    /// An accepted connection was closed right away because of a connection limit
+    fn on_connection_rejected(&self, addr: SocketAddr, reason: RejectReason) {
+        eprintln!("Connection from {} rejected: {:?}", addr, reason);
+    }
+    /// A connection accepted by `Server::serve_tls` failed its TLS handshake or did not complete it in time,
+    /// such connections are never reported as opened
+    #[cfg(feature = "tls")]
+    fn on_tls_handshake_error(&self, addr: SocketAddr, err: tokio::io::Error) {
+        eprintln!("TLS handshake error from {}: {}", addr, err);
+    }
     fn handle_client_error(&self, err: RequestError, status_code: StatusCode) -> Pin<Box<dyn Future<Output = Response> + Send>> {
         Box::pin(async move {
             Response::build().status(status_code).body(format!("invalid request: {err}"))
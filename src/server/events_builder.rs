@@ -1,11 +1,19 @@
-use std::{future::Future, pin::Pin};
+use std::{future::Future, net::SocketAddr, pin::Pin};
 
-use http_tokio::{RequestError, Response, StatusCode};
+use http_tokio::{Request, RequestError, Response, StatusCode};
 
-use crate::server::events::ServerEvents;
+use crate::server::{events::ServerEvents, ListenAddr, RejectReason, RequestSummary};
 
 pub struct ServerEventsBuilder {
+    on_listener_bound: Option<Box<dyn Fn(&ListenAddr) + Send + Sync>>,
+    on_connection_opened: Option<Box<dyn Fn(SocketAddr) + Send + Sync>>,
+    on_connection_closed: Option<Box<dyn Fn(SocketAddr, usize) + Send + Sync>>,
+    on_request_started: Option<Box<dyn Fn(SocketAddr, &Request) + Send + Sync>>,
+    on_request_finished: Option<Box<dyn Fn(&RequestSummary<'_>) + Send + Sync>>,
     on_connection_error: Option<Box<dyn Fn(tokio::io::Error) + Send + Sync>>,
+    on_connection_rejected: Option<Box<dyn Fn(SocketAddr, RejectReason) + Send + Sync>>,
+    #[cfg(feature = "tls")]
+    on_tls_handshake_error: Option<Box<dyn Fn(SocketAddr, tokio::io::Error) + Send + Sync>>,
     handle_client_error: Option<Box<dyn Fn(RequestError, StatusCode) -> Pin<Box<dyn Future<Output = Response> + Send>> + Send + Sync>>,
     handle_timeout: Option<Box<dyn Fn() -> Pin<Box<dyn Future<Output = Response> + Send>> + Send + Sync>>,
 }
@@ -13,12 +21,60 @@ pub struct ServerEventsBuilder {
 impl ServerEventsBuilder {
     pub fn new() -> Self {
         ServerEventsBuilder {
+            on_listener_bound: None,
+            on_connection_opened: None,
+            on_connection_closed: None,
+            on_request_started: None,
+            on_request_finished: None,
             on_connection_error: None,
+            on_connection_rejected: None,
+            #[cfg(feature = "tls")]
+            on_tls_handshake_error: None,
             handle_client_error: None,
             handle_timeout: None,
         }
     }
 
+    pub fn on_listener_bound<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&ListenAddr) + Send + Sync + 'static,
+    {
+        self.on_listener_bound = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_connection_opened<F>(mut self, f: F) -> Self
+    where
+        F: Fn(SocketAddr) + Send + Sync + 'static,
+    {
+        self.on_connection_opened = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_connection_closed<F>(mut self, f: F) -> Self
+    where
+        F: Fn(SocketAddr, usize) + Send + Sync + 'static,
+    {
+        self.on_connection_closed = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_request_started<F>(mut self, f: F) -> Self
+    where
+        F: Fn(SocketAddr, &Request) + Send + Sync + 'static,
+    {
+        self.on_request_started = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_request_finished<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RequestSummary<'_>) + Send + Sync + 'static,
+    {
+        self.on_request_finished = Some(Box::new(f));
+        self
+    }
+
     pub fn on_connection_error<F>(mut self, f: F) -> Self
     where
         F: Fn(tokio::io::Error) + Send + Sync + 'static,
@@ -27,6 +83,23 @@ impl ServerEventsBuilder {
         self
     }
 
+    pub fn on_connection_rejected<F>(mut self, f: F) -> Self
+    where
+        F: Fn(SocketAddr, RejectReason) + Send + Sync + 'static,
+    {
+        self.on_connection_rejected = Some(Box::new(f));
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub fn on_tls_handshake_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(SocketAddr, tokio::io::Error) + Send + Sync + 'static,
+    {
+        self.on_tls_handshake_error = Some(Box::new(f));
+        self
+    }
+
     pub fn handle_client_error<F>(mut self, f: F) -> Self
     where
         F: Fn(RequestError, StatusCode) -> Pin<Box<dyn Future<Output = Response> + Send>> + Send + Sync + 'static,
@@ -45,6 +118,36 @@ impl ServerEventsBuilder {
 }
 
 impl ServerEvents for ServerEventsBuilder {
+    fn on_listener_bound(&self, addr: &ListenAddr) {
+        if let Some(ref f) = self.on_listener_bound {
+            f(addr);
+        }
+    }
+
+    fn on_connection_opened(&self, peer: SocketAddr) {
+        if let Some(ref f) = self.on_connection_opened {
+            f(peer);
+        }
+    }
+
+    fn on_connection_closed(&self, peer: SocketAddr, requests: usize) {
+        if let Some(ref f) = self.on_connection_closed {
+            f(peer, requests);
+        }
+    }
+
+    fn on_request_started(&self, peer: SocketAddr, req: &Request) {
+        if let Some(ref f) = self.on_request_started {
+            f(peer, req);
+        }
+    }
+
+    fn on_request_finished(&self, summary: &RequestSummary<'_>) {
+        if let Some(ref f) = self.on_request_finished {
+            f(summary);
+        }
+    }
+
     fn on_connection_error<'a>(&'a self, err: tokio::io::Error) {
         if let Some(ref f) = self.on_connection_error {
             f(err);
@@ -53,6 +156,23 @@ impl ServerEvents for ServerEventsBuilder {
         }
     }
 
+    fn on_connection_rejected(&self, addr: SocketAddr, reason: RejectReason) {
+        if let Some(ref f) = self.on_connection_rejected {
+            f(addr, reason);
+        } else {
+            ServerEvents::on_connection_rejected(self, addr, reason);
+        }
+    }
+
+    #[cfg(feature = "tls")]
+    fn on_tls_handshake_error(&self, addr: SocketAddr, err: tokio::io::Error) {
+        if let Some(ref f) = self.on_tls_handshake_error {
+            f(addr, err);
+        } else {
+            ServerEvents::on_tls_handshake_error(self, addr, err);
+        }
+    }
+
     fn handle_client_error(&self, err: RequestError, status_code: StatusCode) -> Pin<Box<dyn Future<Output = Response> + Send>> {
         if let Some(ref f) = self.handle_client_error {
             f(err, status_code)
@@ -0,0 +1,89 @@
+use std::{collections::HashMap, net::{IpAddr, SocketAddr}, sync::{Arc, Mutex}, time::Duration};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Why an accepted connection was closed right away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RejectReason {
+    /// The peer ip already has `max_connections_per_ip` open connections
+    PerIpLimit,
+}
+
+/// Delay before accepting again after an accept error (EMFILE and alike),
+/// doubled on each consecutive error up to `max`
+#[derive(Debug, Clone, Copy)]
+pub struct AcceptBackoff {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Default for AcceptBackoff {
+    fn default() -> Self {
+        AcceptBackoff { min: Duration::from_millis(5), max: Duration::from_secs(1) }
+    }
+}
+
+/// Open connections per peer ip
+type PeerCounts = Arc<Mutex<HashMap<IpAddr, usize>>>;
+
+/// Connection ceilings shared by the accept loop and the connection tasks
+pub(crate) struct Limits {
+    connections: Option<Arc<Semaphore>>,
+    per_ip: Option<(usize, PeerCounts)>,
+}
+
+/// Held by a connection task for its whole life, frees its slots on drop
+pub(crate) struct ConnectionPermit {
+    _global: Option<OwnedSemaphorePermit>,
+    peer: Option<(IpAddr, PeerCounts)>,
+}
+
+impl Limits {
+    pub(crate) fn new(max_connections: Option<usize>, max_per_ip: Option<usize>) -> Self {
+        Limits {
+            connections: max_connections.map(|max| Arc::new(Semaphore::new(max))),
+            per_ip: max_per_ip.map(|max| (max, Arc::default())),
+        }
+    }
+
+    /// Waits for a free connection slot, so that nothing is accepted while the server is full
+    pub(crate) async fn reserve(&self) -> Option<OwnedSemaphorePermit> {
+        let semaphore = self.connections.clone()?;
+        semaphore.acquire_owned().await.ok()
+    }
+
+    pub(crate) fn admit(&self, addr: SocketAddr, global: Option<OwnedSemaphorePermit>) -> Result<ConnectionPermit, RejectReason> {
+        let ip = addr.ip();
+        let Some((max, open)) = self.per_ip.as_ref().filter(|_| !ip.is_unspecified()) else {
+            // unix socket peers report the unspecified ip and are not told apart
+            return Ok(ConnectionPermit { _global: global, peer: None });
+        };
+
+        let mut counts = open.lock().unwrap();
+        let count = counts.entry(ip).or_insert(0);
+        if *count >= *max {
+            return Err(RejectReason::PerIpLimit);
+        }
+        *count += 1;
+        Ok(ConnectionPermit { _global: global, peer: Some((ip, open.clone())) })
+    }
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let Some((ip, open)) = &self.peer else { return };
+        let mut counts = open.lock().unwrap();
+        if let Some(count) = counts.get_mut(ip) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(ip);
+            }
+        }
+    }
+}
+
+impl AcceptBackoff {
+    pub(crate) fn next(&self, current: Option<Duration>) -> Duration {
+        current.map_or(self.min, |delay| (delay * 2).min(self.max))
+    }
+}
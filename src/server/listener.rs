@@ -0,0 +1,119 @@
+use std::{fmt::Display, io, net::SocketAddr, path::PathBuf, task::{Context, Poll}};
+use tokio::{io::{AsyncRead, AsyncWrite}, net::TcpListener};
+
+/// A bound socket the `Server` accepts connections from.
+///
+/// Implemented for `TcpListener`, `UnixListener` and for a `Vec` of listeners, which accepts from all of them.
+/// Listeners of different kinds can be served together by erasing their stream type with `boxed`
+pub trait Listener: Send + 'static {
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>>;
+
+    /// Addresses the listener is bound to, reported to `ServerEvents::on_listener_bound`
+    fn local_addrs(&self) -> Vec<ListenAddr> {
+        Vec::new()
+    }
+
+    fn boxed(self) -> BoxedListener where Self: Sized {
+        BoxedListener(Box::new(Erased(self)))
+    }
+}
+
+/// Address of a bound listener
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(Option<PathBuf>), // None for unnamed sockets
+}
+
+impl Display for ListenAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "{addr}"),
+            ListenAddr::Unix(Some(path)) => write!(f, "unix:{}", path.display()),
+            ListenAddr::Unix(None) => write!(f, "unix:(unnamed)"),
+        }
+    }
+}
+
+/// Stream of any connection, as accepted by a `BoxedListener`
+pub trait IoStream: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + 'static> IoStream for T {}
+
+/// A `Listener` with its stream type erased, so that TCP and Unix listeners fit in the same `Vec`
+pub struct BoxedListener(Box<dyn Listener<Stream = Box<dyn IoStream>>>);
+
+impl Listener for BoxedListener {
+    type Stream = Box<dyn IoStream>;
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>> {
+        self.0.poll_accept(cx)
+    }
+
+    fn local_addrs(&self) -> Vec<ListenAddr> {
+        self.0.local_addrs()
+    }
+
+    fn boxed(self) -> BoxedListener {
+        self
+    }
+}
+
+struct Erased<L: Listener>(L);
+
+impl<L: Listener> Listener for Erased<L> {
+    type Stream = Box<dyn IoStream>;
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>> {
+        self.0.poll_accept(cx).map_ok(|(stream, addr)| (Box::new(stream) as Box<dyn IoStream>, addr))
+    }
+
+    fn local_addrs(&self) -> Vec<ListenAddr> {
+        self.0.local_addrs()
+    }
+}
+
+impl Listener for TcpListener {
+    type Stream = tokio::net::TcpStream;
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>> {
+        TcpListener::poll_accept(self, cx)
+    }
+
+    fn local_addrs(&self) -> Vec<ListenAddr> {
+        self.local_addr().map(ListenAddr::Tcp).into_iter().collect()
+    }
+}
+
+#[cfg(unix)]
+impl Listener for tokio::net::UnixListener {
+    type Stream = tokio::net::UnixStream;
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>> {
+        // unix peers have no ip address, connections report the unspecified one which is exempt from per-ip limits
+        tokio::net::UnixListener::poll_accept(self, cx)
+            .map_ok(|(stream, _)| (stream, SocketAddr::from(([0, 0, 0, 0], 0))))
+    }
+
+    fn local_addrs(&self) -> Vec<ListenAddr> {
+        let addr = self.local_addr().map(|addr| addr.as_pathname().map(|path| path.to_path_buf()));
+        addr.map(ListenAddr::Unix).into_iter().collect()
+    }
+}
+
+/// Listeners are polled in turn, the one that just accepted moves to the back
+/// so that a busy listener cannot starve the others
+impl<L: Listener> Listener for Vec<L> {
+    type Stream = L::Stream;
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>> {
+        for i in 0..self.len() {
+            if let Poll::Ready(accepted) = self[i].poll_accept(cx) {
+                self.rotate_left(i + 1);
+                return Poll::Ready(accepted);
+            }
+        }
+        Poll::Pending
+    }
+
+    fn local_addrs(&self) -> Vec<ListenAddr> {
+        self.iter().flat_map(Listener::local_addrs).collect()
+    }
+}
@@ -1,7 +1,15 @@
 mod server;
 mod events;
 mod events_builder;
+mod shutdown;
+mod listener;
+mod limits;
+#[cfg(feature = "tls")]
+pub mod tls;
 
 pub use server::Server;
-pub use events::ServerEvents;
+pub use events::{ServerEvents, RequestSummary};
 pub use events_builder::ServerEventsBuilder;
+pub use shutdown::ShutdownSummary;
+pub use listener::{Listener, ListenAddr, BoxedListener, IoStream};
+pub use limits::{AcceptBackoff, RejectReason};
@@ -1,11 +1,19 @@
-use std::{future::Future, pin::Pin, sync::Arc};
+use std::{collections::HashMap, future::{poll_fn, Future}, net::SocketAddr, pin::Pin, sync::{atomic::{AtomicUsize, Ordering}, Arc}, time::{Duration, Instant}};
 use http_tokio::{server::{Connection, ConnectionEventsHandler, ConnectionHandler, ServerHandler}, BodyReader, Request, RequestError, Response, StatusCode};
-use tokio::net::{TcpListener, ToSocketAddrs};
-use crate::{server::{events::DefaultServerEvents, ServerEvents}, Router};
+use tokio::{io::{AsyncRead, AsyncWrite}, net::{TcpListener, ToSocketAddrs}, sync::watch, task::JoinSet};
+use crate::{server::{events::DefaultServerEvents, limits::{ConnectionPermit, Limits}, shutdown::{ConnectionState, TrackedStream}, AcceptBackoff, Listener, RequestSummary, ServerEvents, ShutdownSummary}, Router};
+#[cfg(feature = "tls")]
+use tokio_rustls::{rustls::ServerConfig, TlsAcceptor};
 
 pub struct Server {
     keep_alive_max: usize,
     keep_alive_timeout: usize,
+    drain_timeout: Duration,
+    max_connections: Option<usize>,
+    max_connections_per_ip: Option<usize>,
+    accept_backoff: AcceptBackoff,
+    #[cfg(feature = "tls")]
+    tls_handshake_timeout: Duration,
     events: Arc<dyn ServerEvents>
 }
 
@@ -15,6 +23,12 @@ impl Server {
             events: Arc::new(DefaultServerEvents),
             keep_alive_max: 100,
             keep_alive_timeout: 5,
+            drain_timeout: Duration::from_secs(30),
+            max_connections: None,
+            max_connections_per_ip: None,
+            accept_backoff: AcceptBackoff::default(),
+            #[cfg(feature = "tls")]
+            tls_handshake_timeout: Duration::from_secs(10),
         }
     }
 
@@ -28,41 +42,354 @@ impl Server {
         self
     }
 
+    /// How long `serve_with_shutdown` waits for in-flight requests before closing their connections
+    pub fn drain_timeout(&mut self, val: Duration) -> &mut Self {
+        self.drain_timeout = val;
+        self
+    }
+
+    /// Stops accepting while `val` connections are open, new clients wait in the listen backlog
+    pub fn max_connections(&mut self, val: usize) -> &mut Self {
+        self.max_connections = Some(val);
+        self
+    }
+
+    /// Closes right away connections from an ip that already has `val` open ones,
+    /// reporting them to `ServerEvents::on_connection_rejected`. Unix socket peers are not limited
+    pub fn max_connections_per_ip(&mut self, val: usize) -> &mut Self {
+        self.max_connections_per_ip = Some(val);
+        self
+    }
+
+    pub fn accept_backoff(&mut self, val: AcceptBackoff) -> &mut Self {
+        self.accept_backoff = val;
+        self
+    }
+
+    /// How long a client has to complete the TLS handshake before its connection is dropped
+    #[cfg(feature = "tls")]
+    pub fn tls_handshake_timeout(&mut self, val: Duration) -> &mut Self {
+        self.tls_handshake_timeout = val;
+        self
+    }
+
     pub fn events(&mut self, events: impl ServerEvents + 'static) {
         self.events = Arc::new(events)
     }
 
     pub async fn serve<A: ToSocketAddrs>(self, addr: A, router: Router) -> Result<(), std::io::Error> {
-        let clone_router = ClonableRouter {
-            inner: Arc::new(router),
-            events: self.events.clone(),
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_listener(listener, router).await
+    }
+
+    /// Serves on an already bound listener: a `TcpListener` bound to port 0 or handed over by socket activation,
+    /// a `UnixListener`, or a `Vec` of listeners to accept on several addresses at once
+    pub async fn serve_listener<L: Listener>(self, listener: L, router: Router) -> Result<(), std::io::Error> {
+        self.serve_listener_with_shutdown(listener, router, std::future::pending()).await.map(drop)
+    }
+
+    /// Serves HTTPS, the TLS handshake of each accepted connection runs in its own task within `tls_handshake_timeout`
+    /// and failures are reported to `ServerEvents::on_tls_handshake_error`
+    #[cfg(feature = "tls")]
+    pub async fn serve_tls<A: ToSocketAddrs>(self, addr: A, config: ServerConfig, router: Router) -> Result<(), std::io::Error> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_tls_listener(listener, config, router).await
+    }
+
+    /// Same as `serve_tls`, on an already bound listener
+    #[cfg(feature = "tls")]
+    pub async fn serve_tls_listener<L: Listener>(self, listener: L, config: ServerConfig, router: Router) -> Result<(), std::io::Error> {
+        self.serve_tls_listener_with_shutdown(listener, config, router, std::future::pending()).await.map(drop)
+    }
+
+    /// Serves until `signal` completes, then stops accepting connections and drains the open ones:
+    /// idle keep-alive connections are closed right away, busy ones finish their request with `Connection: close`
+    /// and whatever is still running after the drain timeout is closed forcibly
+    pub async fn serve_with_shutdown<A, F>(self, addr: A, router: Router, signal: F) -> Result<ShutdownSummary, std::io::Error>
+    where
+        A: ToSocketAddrs,
+        F: Future<Output = ()>,
+    {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_listener_with_shutdown(listener, router, signal).await
+    }
+
+    /// Same as `serve_with_shutdown`, on an already bound listener
+    pub async fn serve_listener_with_shutdown<L, F>(self, listener: L, router: Router, signal: F) -> Result<ShutdownSummary, std::io::Error>
+    where
+        L: Listener,
+        F: Future<Output = ()>,
+    {
+        self.serve_connections(listener, router, signal, |stream, _| std::future::ready(Some(stream))).await
+    }
+
+    /// Same as `serve_with_shutdown`, over TLS
+    #[cfg(feature = "tls")]
+    pub async fn serve_tls_with_shutdown<A, F>(self, addr: A, config: ServerConfig, router: Router, signal: F) -> Result<ShutdownSummary, std::io::Error>
+    where
+        A: ToSocketAddrs,
+        F: Future<Output = ()>,
+    {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_tls_listener_with_shutdown(listener, config, router, signal).await
+    }
+
+    /// Same as `serve_tls_with_shutdown`, on an already bound listener
+    #[cfg(feature = "tls")]
+    pub async fn serve_tls_listener_with_shutdown<L, F>(self, listener: L, config: ServerConfig, router: Router, signal: F) -> Result<ShutdownSummary, std::io::Error>
+    where
+        L: Listener,
+        F: Future<Output = ()>,
+    {
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let (events, timeout) = (self.events.clone(), self.tls_handshake_timeout);
+        let handshake = move |stream, addr| {
+            let (acceptor, events) = (acceptor.clone(), events.clone());
+            async move {
+                let err = match tokio::time::timeout(timeout, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => return Some(stream),
+                    Ok(Err(err)) => err,
+                    Err(_) => std::io::Error::new(std::io::ErrorKind::TimedOut, "TLS handshake timed out"),
+                };
+                events.on_tls_handshake_error(addr, err);
+                None
+            }
+        };
+        self.serve_connections(listener, router, signal, handshake).await
+    }
+}
+
+impl Server {
+    /// Accept loop behind every `serve_*` method, `handshake` turns each accepted stream into the one HTTP is served on
+    /// or drops it by returning `None`. A router `build` would reject is refused before accepting anything
+    async fn serve_connections<L, F, H, Fut, S>(self, listener: L, router: Router, signal: F, handshake: H) -> Result<ShutdownSummary, std::io::Error>
+    where
+        L: Listener,
+        F: Future<Output = ()>,
+        H: Fn(L::Stream, SocketAddr) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Option<S>> + Send,
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let errors = router.errors();
+        if !errors.is_empty() {
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            let message = format!("invalid router: {}", errors.join("; "));
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, message));
+        }
+        let (draining, draining_rx) = watch::channel(false);
+        let clone_router = ClonableRouter::new(router, self.events.clone(), draining_rx);
+
+        self.report_bound(&listener);
+        let mut acceptor = self.acceptor(listener);
+        let mut connections = JoinSet::new();
+        let mut peers = HashMap::new();
+        let mut signal = std::pin::pin!(signal);
+        loop {
+            tokio::select! {
+                _ = &mut signal => break,
+                (stream, addr, permit) = acceptor.next() => {
+                    let (router, handshake) = (clone_router.clone(), handshake.clone());
+                    let keep_alive = (self.keep_alive_max, self.keep_alive_timeout);
+                    let task = connections.spawn(async move {
+                        let stream = handshake(stream, addr).await?;
+                        Some(router.serve(stream, addr, permit, keep_alive).await)
+                    });
+                    peers.insert(task.id(), addr);
+                },
+                Some(joined) = connections.join_next_with_id() => {
+                    peers.remove(&joined.map_or_else(|err| err.id(), |(id, _)| id));
+                }
+            }
+        }
+
+        drop(acceptor);
+        let _ = draining.send(true);
+
+        let mut summary = ShutdownSummary::default();
+        let drain = async {
+            while let Some(joined) = connections.join_next_with_id().await {
+                match joined {
+                    Ok((id, closed)) => {
+                        peers.remove(&id);
+                        match closed {
+                            Some(Closed::Finished) => summary.drained += 1,
+                            Some(Closed::Idle) => summary.idle_closed += 1,
+                            None => {} // failed handshake
+                        }
+                    }
+                    Err(err) => { peers.remove(&err.id()); }
+                }
+            }
         };
-        let server = TcpListener::bind(addr).await?;
+        if tokio::time::timeout(self.drain_timeout, drain).await.is_err() {
+            summary.force_closed = peers.into_values().collect();
+            connections.shutdown().await;
+        }
+        Ok(summary)
+    }
+
+    fn acceptor<L: Listener>(&self, listener: L) -> Acceptor<L> {
+        Acceptor {
+            listener,
+            limits: Limits::new(self.max_connections, self.max_connections_per_ip),
+            backoff: self.accept_backoff,
+            events: self.events.clone(),
+            delay: None,
+            retry_at: None,
+        }
+    }
+
+    fn report_bound(&self, listener: &impl Listener) {
+        for addr in listener.local_addrs() {
+            self.events.on_listener_bound(&addr);
+        }
+    }
+}
+
+/// Accepts the connections allowed by the limits. Accept errors are retried after a growing delay
+/// which lives here rather than in `next`, so that it survives the `select!` iterations dropping `next`
+struct Acceptor<L: Listener> {
+    listener: L,
+    limits: Limits,
+    backoff: AcceptBackoff,
+    events: Arc<dyn ServerEvents>,
+    delay: Option<Duration>, // since the last consecutive accept error
+    retry_at: Option<tokio::time::Instant>,
+}
+
+impl<L: Listener> Acceptor<L> {
+    async fn next(&mut self) -> (L::Stream, SocketAddr, ConnectionPermit) {
         loop {
-            match server.accept().await {
+            if let Some(retry_at) = self.retry_at {
+                tokio::time::sleep_until(retry_at).await;
+                self.retry_at = None;
+            }
+            let reserved = self.limits.reserve().await;
+            match poll_fn(|cx| self.listener.poll_accept(cx)).await {
                 Ok((stream, addr)) => {
-                    let conn = Connection::new(stream, addr)
-                        .keep_alive_max(self.keep_alive_max)
-                        .keep_alive_timeout(self.keep_alive_timeout);
-                    tokio::task::spawn(conn.handle_with(clone_router.clone()));
+                    self.delay = None;
+                    match self.limits.admit(addr, reserved) {
+                        Ok(permit) => return (stream, addr, permit),
+                        Err(reason) => self.events.on_connection_rejected(addr, reason),
+                    }
+                }
+                Err(err) => {
+                    self.events.on_connection_error(err);
+                    let delay = self.accept_delay();
+                    self.retry_at = Some(tokio::time::Instant::now() + delay);
                 }
-                Err(err) => self.events.on_connection_error(err)
             }
         }
     }
+
+    fn accept_delay(&mut self) -> Duration {
+        let delay = self.backoff.next(self.delay);
+        self.delay = Some(delay);
+        delay
+    }
+}
+
+enum Closed {
+    Finished,
+    Idle,
+}
+
+/// Alive as long as its connection task, reports the connection as closed when dropped
+struct OpenConnection {
+    peer: SocketAddr,
+    events: Arc<dyn ServerEvents>,
+    served: Arc<AtomicUsize>,
+    _permit: ConnectionPermit,
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.events.on_connection_closed(self.peer, self.served.load(Ordering::SeqCst));
+    }
 }
 
 #[derive(Clone)]
 struct ClonableRouter {
     inner: Arc<Router>,
     events: Arc<dyn ServerEvents>,
+    draining: watch::Receiver<bool>,
+    peer: SocketAddr,
+    state: Arc<ConnectionState>,
+    served: Arc<AtomicUsize>, // requests answered on the connection
+}
+
+impl ClonableRouter {
+    fn new(router: Router, events: Arc<dyn ServerEvents>, draining: watch::Receiver<bool>) -> Self {
+        ClonableRouter {
+            inner: Arc::new(router),
+            events,
+            draining,
+            peer: SocketAddr::from(([0, 0, 0, 0], 0)), // set per connection by `open`
+            state: Arc::default(),
+            served: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Serves a newly accepted connection with its own counters until it closes
+    async fn serve<S>(self, stream: S, peer: SocketAddr, permit: ConnectionPermit, (keep_alive_max, keep_alive_timeout): (usize, usize)) -> Closed
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let handler = ClonableRouter {
+            peer,
+            state: Arc::default(),
+            served: Arc::new(AtomicUsize::new(0)),
+            ..self
+        };
+        handler.events.on_connection_opened(peer);
+        let open = OpenConnection { peer, events: handler.events.clone(), served: handler.served.clone(), _permit: permit };
+        let conn = Connection::new(TrackedStream::new(stream, handler.state.clone()), peer)
+            .keep_alive_max(keep_alive_max)
+            .keep_alive_timeout(keep_alive_timeout);
+        handler.clone().run(conn.handle_with(handler), open).await
+    }
+
+    /// Drives the connection until it ends on its own, or until the server is draining
+    /// and the connection waits for a request with its last response written
+    async fn run(mut self, conn: impl Future<Output = ()>, _open: OpenConnection) -> Closed {
+        let idle = async move {
+            if self.draining.wait_for(|draining| *draining).await.is_err() {
+                std::future::pending::<()>().await // the server never drains
+            }
+            self.state.idle().await
+        };
+        tokio::select! {
+            _ = conn => Closed::Finished,
+            _ = idle => Closed::Idle,
+        }
+    }
 }
 
 impl<'a> ServerHandler<'a> for ClonableRouter {}
 
 impl<'a> ConnectionHandler<'a> for ClonableRouter {
     fn handle(&'a self, request: &'a Request, payload: &'a BodyReader) -> Pin<Box<dyn Future<Output = Response> + Send + 'a>> {
-        Box::pin(async move { self.inner.handle_request(request, payload).await })
+        Box::pin(async move {
+            let _request = self.state.request();
+            self.events.on_request_started(self.peer, request);
+            let started = Instant::now();
+
+            let (mut res, matched) = self.inner.handle_matched(request, payload).await;
+            if *self.draining.borrow() {
+                res.headers.insert("Connection", "close");
+            }
+
+            self.events.on_request_finished(&RequestSummary {
+                peer: self.peer,
+                method: &request.method,
+                path: &request.path,
+                route: matched.as_ref(),
+                status: res.status,
+                duration: started.elapsed(),
+            });
+            self.served.fetch_add(1, Ordering::SeqCst);
+            res
+        })
     }
 }
 
@@ -74,4 +401,4 @@ impl ConnectionEventsHandler for ClonableRouter {
     fn handle_timeout(&self) -> Pin<Box<dyn Future<Output = Response> + Send>> {
         self.events.handle_timeout()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,115 @@
+use std::{io, net::SocketAddr, pin::{pin, Pin}, sync::{atomic::{AtomicBool, Ordering}, Arc}, task::{Context, Poll}};
+use tokio::{io::{AsyncRead, AsyncWrite, ReadBuf}, sync::Notify};
+
+/// What happened to the open connections when `Server::serve_with_shutdown` stopped
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownSummary {
+    /// Connections that finished their in-flight requests within the drain timeout
+    pub drained: usize,
+    /// Keep-alive connections closed while waiting for a request
+    pub idle_closed: usize,
+    /// Connections still busy when the drain timeout expired
+    pub force_closed: Vec<SocketAddr>,
+}
+
+/// Where a connection stands between its requests, so that a shutdown only closes it
+/// once its last response is written and nothing of the next request was read
+#[derive(Default)]
+pub(crate) struct ConnectionState {
+    busy: AtomicBool,    // bytes of a request were read and its response is not built yet
+    waiting: AtomicBool, // the last read on the socket had nothing to return
+    changed: Notify,
+}
+
+impl ConnectionState {
+    fn is_idle(&self) -> bool {
+        !self.busy.load(Ordering::SeqCst) && self.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Completes once the connection waits for a request with nothing left to answer
+    pub(crate) async fn idle(&self) {
+        loop {
+            let mut changed = pin!(self.changed.notified());
+            changed.as_mut().enable();
+            if self.is_idle() {
+                return;
+            }
+            changed.await;
+        }
+    }
+
+    /// Marks a request as in flight until the guard drops, even when its task is aborted
+    pub(crate) fn request(self: &Arc<Self>) -> RequestGuard {
+        self.read();
+        RequestGuard(self.clone())
+    }
+
+    fn read(&self) {
+        self.busy.store(true, Ordering::SeqCst);
+        self.waiting.store(false, Ordering::SeqCst);
+    }
+
+    fn wait(&self) {
+        self.waiting.store(true, Ordering::SeqCst);
+        if self.is_idle() {
+            self.changed.notify_waiters();
+        }
+    }
+}
+
+pub(crate) struct RequestGuard(Arc<ConnectionState>);
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        // the response is written next, the connection is idle again once it reads from the socket
+        self.0.waiting.store(false, Ordering::SeqCst);
+        self.0.busy.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Stream of a connection reporting its reads to the `ConnectionState`
+pub(crate) struct TrackedStream<S> {
+    inner: S,
+    state: Arc<ConnectionState>,
+}
+
+impl<S> TrackedStream<S> {
+    pub(crate) fn new(inner: S, state: Arc<ConnectionState>) -> Self {
+        TrackedStream { inner, state }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for TrackedStream<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        match &poll {
+            Poll::Ready(Ok(())) if buf.filled().len() > filled => self.state.read(),
+            Poll::Pending => self.state.wait(),
+            _ => {}
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for TrackedStream<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
@@ -0,0 +1,83 @@
+use std::{collections::HashMap, fs::File, io::{self, BufReader}, path::Path, sync::Arc};
+use tokio_rustls::rustls::{
+    crypto::aws_lc_rs::sign::any_supported_type,
+    pki_types::{CertificateDer, PrivateKeyDer},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+
+pub use tokio_rustls::rustls::{self, ServerConfig};
+
+/// Certificate chain of a PEM file
+pub fn load_certs(path: impl AsRef<Path>) -> io::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::certs(&mut reader).collect()
+}
+
+/// First private key of a PEM file, PKCS#1, PKCS#8 or SEC1
+pub fn load_key(path: impl AsRef<Path>) -> io::Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path.as_ref())?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("no private key found in {:?}", path.as_ref())))
+}
+
+/// `ServerConfig` serving a single certificate, loaded from PEM files
+pub fn server_config(cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> io::Result<ServerConfig> {
+    let mut config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(load_certs(cert_path)?, load_key(key_path)?)
+        .map_err(invalid_data)?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Picks the certificate by the server name the client sent (SNI),
+/// falling back to the default one for unknown names and clients without SNI
+#[derive(Debug, Default)]
+pub struct SniCertificates {
+    by_name: HashMap<String, Arc<CertifiedKey>>,
+    default: Option<Arc<CertifiedKey>>,
+}
+
+impl SniCertificates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(mut self, server_name: &str, cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> io::Result<Self> {
+        let key = certified_key(cert_path, key_path)?;
+        self.by_name.insert(server_name.to_lowercase(), key);
+        Ok(self)
+    }
+
+    pub fn default_cert(mut self, cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> io::Result<Self> {
+        self.default = Some(certified_key(cert_path, key_path)?);
+        Ok(self)
+    }
+
+    pub fn into_config(self) -> ServerConfig {
+        let mut config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(self));
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        config
+    }
+}
+
+impl ResolvesServerCert for SniCertificates {
+    fn resolve(&self, hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        hello.server_name()
+            .and_then(|name| self.by_name.get(&name.to_lowercase()))
+            .or(self.default.as_ref())
+            .cloned()
+    }
+}
+
+fn certified_key(cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> io::Result<Arc<CertifiedKey>> {
+    let signing_key = any_supported_type(&load_key(key_path)?).map_err(invalid_data)?;
+    Ok(Arc::new(CertifiedKey::new(load_certs(cert_path)?, signing_key)))
+}
+
+fn invalid_data(err: rustls::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
@@ -0,0 +1,170 @@
+use serde::{de::DeserializeOwned, Serialize};
+use http_tokio::{BodyReader, Headers, Request, Response, StatusCode};
+use crate::Router;
+
+/// Drives a `Router` in process, without sockets:
+/// ```ignore
+/// let client = TestClient::new(router);
+/// client.post("/users").json(&user).send().await.assert_status(201);
+/// ```
+pub struct TestClient {
+    router: Router,
+}
+
+impl TestClient {
+    pub fn new(router: Router) -> Self {
+        TestClient { router }
+    }
+
+    pub fn request(&self, method: &str, path: &str) -> TestRequest<'_> {
+        TestRequest {
+            client: self,
+            method: method.to_uppercase(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, path: &str) -> TestRequest<'_> {
+        self.request("GET", path)
+    }
+
+    pub fn post(&self, path: &str) -> TestRequest<'_> {
+        self.request("POST", path)
+    }
+
+    pub fn put(&self, path: &str) -> TestRequest<'_> {
+        self.request("PUT", path)
+    }
+
+    pub fn patch(&self, path: &str) -> TestRequest<'_> {
+        self.request("PATCH", path)
+    }
+
+    pub fn delete(&self, path: &str) -> TestRequest<'_> {
+        self.request("DELETE", path)
+    }
+
+    pub fn head(&self, path: &str) -> TestRequest<'_> {
+        self.request("HEAD", path)
+    }
+
+    pub fn options(&self, path: &str) -> TestRequest<'_> {
+        self.request("OPTIONS", path)
+    }
+}
+
+pub struct TestRequest<'c> {
+    client: &'c TestClient,
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl TestRequest<'_> {
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Serializes `body` as JSON, setting the `Content-Type` unless already given
+    pub fn json(self, body: &impl Serialize) -> Self {
+        let body = serde_json::to_vec(body).expect("test request body should serialize to JSON");
+        self.with_content_type("application/json").body(body)
+    }
+
+    /// Encodes the `(key, value)` pairs as an urlencoded form, setting the `Content-Type` unless already given
+    pub fn form<K: AsRef<str>, V: AsRef<str>>(self, pairs: impl IntoIterator<Item = (K, V)>) -> Self {
+        let body = form_urlencoded::Serializer::new(String::new()).extend_pairs(pairs).finish();
+        self.with_content_type("application/x-www-form-urlencoded").body(body)
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let mut headers = Headers::new();
+        for (name, value) in &self.headers {
+            headers.insert(name, value);
+        }
+        if !self.body.is_empty() && !self.has_header("Content-Length") {
+            headers.insert("Content-Length", self.body.len());
+        }
+
+        let req = Request::new(&self.method, &self.path, headers);
+        let payload = BodyReader::new(self.body);
+        let res = self.client.router.handle_request(&req, &payload).await;
+        TestResponse { inner: res }
+    }
+
+    fn has_header(&self, name: &str) -> bool {
+        self.headers.iter().any(|(key, _)| key.eq_ignore_ascii_case(name))
+    }
+
+    fn with_content_type(self, content_type: &str) -> Self {
+        match self.has_header("Content-Type") {
+            true => self,
+            false => self.header("Content-Type", content_type),
+        }
+    }
+}
+
+/// Response of a `TestRequest`, the `assert_*` methods panic with the response body on mismatch
+#[derive(Debug)]
+pub struct TestResponse {
+    inner: Response,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.inner.status
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.inner.headers.get(name)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.inner.body
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.inner.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.inner.body)
+            .unwrap_or_else(|err| panic!("response body is not the expected JSON ({err}): {}", self.text()))
+    }
+
+    pub fn into_inner(self) -> Response {
+        self.inner
+    }
+
+    pub fn assert_status(&self, status: impl Into<StatusCode>) -> &Self {
+        let status = status.into();
+        assert_eq!(self.inner.status, status, "unexpected status, body: {}", self.text());
+        self
+    }
+
+    pub fn assert_header(&self, name: &str, value: &str) -> &Self {
+        assert_eq!(self.header(name), Some(value), "unexpected {name:?} header, body: {}", self.text());
+        self
+    }
+
+    /// Compares the JSON body with `expected` regardless of formatting and key order
+    pub fn assert_json(&self, expected: &impl Serialize) -> &Self {
+        let expected = serde_json::to_value(expected).expect("expected JSON should serialize");
+        assert_eq!(self.json::<serde_json::Value>(), expected, "unexpected JSON body");
+        self
+    }
+
+    pub fn assert_text(&self, expected: &str) -> &Self {
+        assert_eq!(self.text(), expected, "unexpected body");
+        self
+    }
+}
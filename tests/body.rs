@@ -0,0 +1,74 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, node::*, extractors::{BodyOwned, Json, Multipart, DEFAULT_MAX_BODY_SIZE}, result::HttpResult, route, test_client::TestClient};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Payload {
+    a: u32,
+}
+
+#[route]
+async fn json(body: Json<Payload>) -> String {
+    body.a.to_string()
+}
+
+#[route]
+async fn raw(body: BodyOwned) -> String {
+    body.bytes().len().to_string()
+}
+
+#[route]
+async fn upload(mut multipart: Multipart<'_>) -> HttpResult<String> {
+    let mut sizes = Vec::new();
+    while let Some(field) = multipart.next_field().await? {
+        sizes.push(field.bytes().await?.len().to_string());
+    }
+    Ok(sizes.join(","))
+}
+
+#[tokio::test]
+async fn enforces_the_content_type() {
+    let client = TestClient::new(Router::new().at("/json", post(json)));
+    client.post("/json").header("Content-Type", "application/json; charset=utf-8").body(r#"{"a":1}"#).send().await
+        .assert_status(200)
+        .assert_text("1");
+    client.post("/json").header("Content-Type", "text/plain").body(r#"{"a":1}"#).send().await.assert_status(415);
+    client.post("/json").body(r#"{"a":1}"#).send().await.assert_status(415);
+}
+
+#[tokio::test]
+async fn rejects_bodies_over_the_scope_limit() {
+    let router = Router::new()
+        .max_body_size(10)
+        .at("/json", post(json))
+        .at("/upload", post(upload))
+        .at("/big", scope("/").max_body_size(1000).at("/json", post(json)));
+    let client = TestClient::new(router);
+    let body = r#"{"a":1,"b":"xxxxxx"}"#;
+
+    client.post("/json").json(&serde_json::json!({"a": 1})).send().await.assert_status(200);
+    client.post("/json").header("Content-Type", "application/json").body(body).send().await.assert_status(413);
+    client.post("/big/json").header("Content-Type", "application/json").body(body).send().await.assert_status(200);
+
+    let multipart = "--XX\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nhello\r\n--XX--\r\n";
+    client.post("/upload").header("Content-Type", "multipart/form-data; boundary=XX").body(multipart).send().await.assert_status(413);
+}
+
+#[tokio::test]
+async fn applies_a_default_limit() {
+    let client = TestClient::new(Router::new().at("/raw", post(raw)));
+    client.post("/raw").body(vec![b'x'; DEFAULT_MAX_BODY_SIZE]).send().await.assert_status(200);
+    client.post("/raw").body(vec![b'x'; DEFAULT_MAX_BODY_SIZE + 1]).send().await.assert_status(413);
+    client.post("/raw").header("Content-Length", "1").body(vec![b'x'; DEFAULT_MAX_BODY_SIZE + 1]).send().await.assert_status(413);
+}
+
+#[tokio::test]
+async fn reads_multipart_parts_spanning_several_chunks() {
+    let client = TestClient::new(Router::new().at("/upload", post(upload)));
+    let file = "x".repeat(10_000);
+    let body = format!("--XX\r\nContent-Disposition: form-data; name=\"f\"\r\n\r\n{file}\r\n--XX\r\nContent-Disposition: form-data; name=\"g\"\r\n\r\nab\r\n--XX--\r\n");
+    client.post("/upload").header("Content-Type", "multipart/form-data; boundary=XX").body(body).send().await
+        .assert_status(200)
+        .assert_text("10000,2");
+}
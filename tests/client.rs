@@ -0,0 +1,26 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, node::*, extractors::Json, route, test_client::TestClient};
+use serde_json::{json, Value};
+
+#[route]
+async fn echo(body: Json<Value>) -> Json<Value> {
+    body
+}
+
+#[tokio::test]
+async fn sends_json_and_reads_response() {
+    let client = TestClient::new(Router::new().at("/echo", post(echo)));
+    client.post("/echo").json(&json!({"a": 1})).send().await
+        .assert_status(200)
+        .assert_header("Content-Type", "application/json")
+        .assert_json(&json!({"a": 1}));
+}
+
+#[tokio::test]
+async fn reports_router_errors() {
+    let client = TestClient::new(Router::new().at("/echo", post(echo)));
+    client.get("/echo").send().await.assert_status(405);
+    client.get("/nope").send().await.assert_status(404);
+    client.post("/echo").header("Content-Type", "text/plain").body("x").send().await.assert_status(415);
+}
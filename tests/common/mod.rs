@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+
+use std::{future::Future, io, net::SocketAddr};
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request};
+use http_tokio_router::{Router, extractors::{FromRequest, RequestParams}, result::{HandlerResult, IntoRouteResult}, server::{Server, ShutdownSummary}};
+use tokio::{io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt}, net::TcpListener, sync::oneshot, task::JoinHandle};
+
+/// Handler answering with its name followed by the captured params, in capture order
+pub fn tag(name: &'static str) -> impl for<'a> Fn(&'a Request, &'a BodyReader) -> HandlerResult<'a> + Send + Sync + 'static {
+    move |req, body| Box::pin(async move {
+        let params = RequestParams::from_req(req, body).await?;
+        let params: Vec<String> = params.ordered().map(|(key, value)| format!("{key}={value}")).collect();
+        IntoRouteResult::into(format!("{name} {}", params.join(",")).trim_end().to_string())
+    })
+}
+
+/// Shutdown signal handed to the `serve_*_with_shutdown` methods
+pub type Signal = BoxFuture<'static, ()>;
+
+/// Server spawned in the background, serving until `shutdown` is sent
+pub struct Running {
+    pub addr: SocketAddr,
+    pub shutdown: oneshot::Sender<()>,
+    pub server: JoinHandle<ShutdownSummary>,
+}
+
+impl Running {
+    pub async fn stop(self) -> ShutdownSummary {
+        self.shutdown.send(()).unwrap();
+        self.server.await.unwrap()
+    }
+}
+
+/// Serves `router` with `server` on a loopback port
+pub async fn start(server: Server, router: Router) -> Running {
+    start_with(|listener, signal| server.serve_listener_with_shutdown(listener, router, signal)).await
+}
+
+/// Binds a loopback port and spawns `serve` on it
+pub async fn start_with<F, Fut>(serve: F) -> Running
+where
+    F: FnOnce(TcpListener, Signal) -> Fut,
+    Fut: Future<Output = io::Result<ShutdownSummary>> + Send + 'static,
+{
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown, server) = spawn(|signal| serve(listener, signal));
+    Running { addr, shutdown, server }
+}
+
+/// Spawns `serve` with a signal completing once the returned sender is used, serving must not fail
+pub fn spawn<F, Fut>(serve: F) -> (oneshot::Sender<()>, JoinHandle<ShutdownSummary>)
+where
+    F: FnOnce(Signal) -> Fut,
+    Fut: Future<Output = io::Result<ShutdownSummary>> + Send + 'static,
+{
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let serving = serve(Box::pin(async move { let _ = signal.await; }));
+    (shutdown, tokio::spawn(async move { serving.await.unwrap() }))
+}
+
+/// Raw `GET` request for `path`, asking the server to close the connection after it when `close`
+pub fn raw_get(path: &str, close: bool) -> String {
+    let connection = if close { "Connection: close\r\n" } else { "" };
+    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n{connection}\r\n")
+}
+
+/// Writes `request` and reads the response up to `end`
+pub async fn fetch<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, request: &str, end: &str) -> String {
+    stream.write_all(request.as_bytes()).await.unwrap();
+    read_until(stream, end).await
+}
+
+pub async fn read_until<S: AsyncRead + Unpin>(stream: &mut S, end: &str) -> String {
+    let mut raw = Vec::new();
+    while !raw.ends_with(end.as_bytes()) {
+        let mut buf = [0; 1024];
+        let read = stream.read(&mut buf).await.unwrap();
+        assert!(read > 0, "connection closed early: {}", String::from_utf8_lossy(&raw));
+        raw.extend_from_slice(&buf[..read]);
+    }
+    String::from_utf8(raw).unwrap()
+}
+
+pub async fn read_to_end<S: AsyncRead + Unpin>(stream: &mut S) -> String {
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await.unwrap();
+    String::from_utf8(raw).unwrap()
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::tag;
+use http_tokio_router::{Router, node::*, error::{RegisterError, UrlError}, pattern::Pattern, test_client::TestClient};
+use serde_json::json;
+
+fn router() -> Router {
+    Router::new()
+        .at("/users/{id:int}", get(tag("int")).name("user"))
+        .at("/users/{id:uuid}", get(tag("uuid")))
+        .at("/users/{slug:[a-z0-9-]+}", get(tag("slug")).name("slug"))
+        .at("/years/{year:\\d{4}}", post(tag("year")))
+        .at("/users/{name}", delete(tag("any")))
+}
+
+#[tokio::test]
+async fn routes_segments_by_constraint() {
+    let client = TestClient::new(router().build().unwrap());
+
+    client.get("/users/42").send().await.assert_text("int id=42");
+    client.get("/users/-42").send().await.assert_text("int id=-42");
+    client.get("/users/+42").send().await.assert_status(405);
+    client.get("/users/123e4567-e89b-12d3-a456-426614174000").send().await.assert_text("uuid id=123e4567-e89b-12d3-a456-426614174000");
+    client.get("/users/hello-world").send().await.assert_text("slug slug=hello-world");
+    client.get("/users/Hello").send().await.assert_status(405);
+    client.delete("/users/Hello").send().await.assert_text("any name=Hello");
+    client.post("/years/2024").send().await.assert_text("year year=2024");
+    client.post("/years/24").send().await.assert_status(404);
+}
+
+#[tokio::test]
+async fn validates_url_params_against_their_constraint() {
+    let router = router();
+    assert_eq!(router.url_for("user", &json!({"id": 5})).unwrap(), "/users/5");
+    assert_eq!(router.url_for("slug", &json!({"slug": "hello-world"})).unwrap(), "/users/hello-world");
+    let err = router.url_for("user", &json!({"id": "five"})).unwrap_err();
+    assert!(matches!(&err, UrlError::ConstraintMismatch(param, constraint) if param == "id" && constraint == "int"), "{err:?}");
+    let err = router.url_for("slug", &json!({"slug": "Hello"})).unwrap_err();
+    assert!(matches!(&err, UrlError::ConstraintMismatch(param, _) if param == "slug"), "{err:?}");
+}
+
+#[test]
+fn parses_constraints() {
+    assert!(Pattern::parse("GET:/a/{b:int}").is_ok());
+    assert!(Pattern::parse("/a/{b:(}").is_err());
+    assert!(Pattern::parse("/a/{b:}").is_err());
+    assert_eq!(Pattern::parse("/a/{b:int}").unwrap().method, "ALL");
+}
+
+#[test]
+fn reports_conflicting_params_under_the_same_constraint() {
+    let errors = Router::new().at("/a/{x:int}", get(tag("a"))).at("/a/{y:int}", post(tag("b"))).build().err().unwrap();
+    assert!(matches!(&errors[..], [RegisterError::DuplicateDynamicSegment(..)]), "{errors:?}");
+}
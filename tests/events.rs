@@ -0,0 +1,36 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use common::{raw_get, read_to_end, start};
+use http_tokio_router::{Router, node::*, route, server::{Server, ServerEventsBuilder}};
+use tokio::{io::AsyncWriteExt, net::TcpStream};
+
+#[route]
+async fn user() -> &'static str {
+    "user"
+}
+
+#[tokio::test]
+async fn reports_the_matched_route_of_finished_requests() {
+    let finished = Arc::new(Mutex::new(Vec::new()));
+    let summaries = finished.clone();
+    let mut server = Server::new();
+    server.events(ServerEventsBuilder::new().on_request_finished(move |summary| {
+        let route = summary.route.map(|route| (route.pattern().to_string(), route.name().map(str::to_string)));
+        summaries.lock().unwrap().push((summary.path.to_string(), route, summary.status.as_u16()));
+    }));
+
+    let router = Router::new().at("/users/{id}", get(user).name("user"));
+    let running = start(server, router).await;
+    for path in ["/users/7", "/missing"] {
+        let mut stream = TcpStream::connect(running.addr).await.unwrap();
+        stream.write_all(raw_get(path, true).as_bytes()).await.unwrap();
+        read_to_end(&mut stream).await;
+    }
+    running.stop().await;
+
+    assert_eq!(*finished.lock().unwrap(), [
+        ("/users/7".to_string(), Some(("/users/{id}".to_string(), Some("user".to_string()))), 200),
+        ("/missing".to_string(), None, 404),
+    ]);
+}
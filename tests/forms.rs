@@ -0,0 +1,60 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, node::*, extractors::{Form, Multipart}, result::HttpResult, route, test_client::TestClient};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Login {
+    user: String,
+    remember: Option<bool>,
+}
+
+#[route]
+async fn login(form: Form<Login>) -> String {
+    format!("{} {:?}", form.user, form.remember)
+}
+
+#[route]
+async fn upload(mut multipart: Multipart<'_>) -> HttpResult<String> {
+    let mut fields = Vec::new();
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        let file_name = field.file_name().map(String::from);
+        fields.push(format!("{name}:{file_name:?}={}", field.text().await?));
+    }
+    Ok(fields.join(" "))
+}
+
+#[tokio::test]
+async fn deserializes_urlencoded_forms() {
+    let client = TestClient::new(Router::new().at("/login", post(login)));
+    client.post("/login").form([("user", "a b"), ("remember", "true")]).send().await.assert_status(200).assert_text("a b Some(true)");
+    client.post("/login").form([("remember", "true")]).send().await.assert_status(400);
+    client.post("/login").json(&serde_json::json!({"user": "a"})).send().await.assert_status(415);
+}
+
+#[tokio::test]
+async fn reads_multipart_fields() {
+    let client = TestClient::new(Router::new().at("/upload", post(upload)));
+    let body = concat!(
+        "--XX\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nhello\r\n",
+        "--XX\r\nContent-Disposition: form-data; name=\"b\"; filename=\"f.txt\"\r\nContent-Type: text/plain\r\n\r\nworld\r\n",
+        "--XX--\r\n",
+    );
+    client.post("/upload").header("Content-Type", "multipart/form-data; boundary=XX").body(body).send().await
+        .assert_status(200)
+        .assert_text(r#"a:None=hello b:Some("f.txt")=world"#);
+    client.post("/upload").header("Content-Type", "text/plain").body(body).send().await.assert_status(400);
+}
+
+#[tokio::test]
+async fn parses_quoted_header_params() {
+    let client = TestClient::new(Router::new().at("/upload", post(upload)));
+    let body = concat!(
+        "--a;b=c\r\nContent-Disposition: form-data; filename=\"x; name=y.txt\"; name=\"say \\\"hi\\\"\"\r\n\r\nhey\r\n",
+        "--a;b=c--\r\n",
+    );
+    client.post("/upload").header("Content-Type", "multipart/form-data; charset=utf-8; boundary=\"a;b=c\"").body(body).send().await
+        .assert_status(200)
+        .assert_text(r#"say "hi":Some("x; name=y.txt")=hey"#);
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use http_tokio::Request;
+use common::tag;
+use http_tokio_router::{Router, node::*, guards::*, error::RegisterError, test_client::TestClient};
+
+#[tokio::test]
+async fn routes_by_host_content_type_and_query() {
+    let router = Router::new()
+        .add(scope("/").guard(host("api.example.com")).at("/x", get(tag("api"))))
+        .add(scope("/").guard(host("{tenant}.example.com")).at("/x", get(tag("tenant"))))
+        .at("/x", get(tag("plain")))
+        .at("/up", post(tag("json")).guard(content_type("application/json")))
+        .at("/up", post(tag("form")).guard(content_type("application/x-www-form-urlencoded")))
+        .build()
+        .unwrap();
+    let client = TestClient::new(router);
+
+    client.get("/x").header("Host", "API.example.com:8080").send().await.assert_text("api");
+    client.get("/x").header("Host", "acme.example.com").send().await.assert_text("tenant tenant=acme");
+    client.get("/x").header("Host", "example.com").send().await.assert_text("plain");
+    client.post("/up").header("Content-Type", "application/json; charset=utf-8").body("{}").send().await.assert_text("json");
+    client.post("/up").form([("a", "b")]).send().await.assert_text("form");
+    client.post("/up").header("Content-Type", "text/plain").send().await.assert_status(404);
+}
+
+#[tokio::test]
+async fn combines_guards() {
+    let router = Router::new()
+        .at("/q", get(tag("q")).guard(all((query("a"), not(header_exists("X-No")), |req: &Request| req.path.len() > 3))))
+        .at("/any", get(tag("any")).guard(any((header("X-A", "1"), header("X-B", "2")))));
+    let client = TestClient::new(router);
+
+    client.get("/q?a").send().await.assert_text("q");
+    client.get("/q?a").header("X-No", "1").send().await.assert_status(404);
+    client.get("/q?b=1").send().await.assert_status(404);
+    client.get("/any").header("X-B", "2").send().await.assert_text("any");
+    client.get("/any").header("X-B", "3").send().await.assert_status(404);
+    client.post("/any").header("X-B", "2").send().await.assert_status(405);
+}
+
+#[tokio::test]
+async fn keeps_the_params_of_the_matching_alternative_only() {
+    let first = all((host("{tenant}.example.com"), header("X-Tenant", "1")));
+    let router = Router::new()
+        .at("/x", get(tag("x")).guard(any((first, host("{region}.example.com")))))
+        .at("/y", get(tag("y")).guard(not(host("{tenant}.example.com"))));
+    let client = TestClient::new(router);
+
+    client.get("/x").header("Host", "eu.example.com").send().await.assert_text("x region=eu");
+    client.get("/x").header("Host", "acme.example.com").header("X-Tenant", "1").send().await.assert_text("x tenant=acme");
+    client.get("/y").header("Host", "example.org").send().await.assert_text("y");
+}
+
+#[tokio::test]
+async fn reports_invalid_guards() {
+    let errors = Router::new().at("/a", get(tag("a")).guard(host("bad_host"))).build().err().unwrap();
+    assert!(matches!(&errors[..], [RegisterError::InvalidGuard(_)]), "{errors:?}");
+}
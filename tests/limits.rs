@@ -0,0 +1,93 @@
+mod common;
+
+use std::{io, net::SocketAddr, sync::{Arc, Mutex}, task::{Context, Poll}, time::{Duration, Instant}};
+use common::{fetch, raw_get, read_to_end, spawn, start, Running};
+use http_tokio_router::{Router, node::*, route, server::{AcceptBackoff, Listener, RejectReason, Server, ServerEventsBuilder}};
+use tokio::{io::{duplex, DuplexStream}, net::TcpStream, time::{sleep, timeout}};
+
+#[route]
+async fn hello() -> &'static str {
+    "hello"
+}
+
+async fn start_hello(server: Server) -> Running {
+    start(server, Router::new().at("/hello", get(hello))).await
+}
+
+async fn fetch_hello(stream: &mut TcpStream) -> String {
+    fetch(stream, &raw_get("/hello", false), "hello").await
+}
+
+#[tokio::test]
+async fn rejects_connections_over_the_per_ip_limit() {
+    let rejected = Arc::new(Mutex::new(Vec::new()));
+    let mut server = Server::new();
+    server.max_connections_per_ip(1);
+    let reasons = rejected.clone();
+    server.events(ServerEventsBuilder::new().on_connection_rejected(move |_, reason| reasons.lock().unwrap().push(reason)));
+    let running = start_hello(server).await;
+
+    let mut first = TcpStream::connect(running.addr).await.unwrap();
+    assert!(fetch_hello(&mut first).await.starts_with("HTTP/1.1 200"));
+    let mut second = TcpStream::connect(running.addr).await.unwrap();
+    assert_eq!(read_to_end(&mut second).await, "");
+    assert_eq!(*rejected.lock().unwrap(), [RejectReason::PerIpLimit]);
+
+    drop(first);
+    sleep(Duration::from_millis(50)).await;
+    let mut third = TcpStream::connect(running.addr).await.unwrap();
+    assert!(fetch_hello(&mut third).await.starts_with("HTTP/1.1 200"));
+}
+
+#[tokio::test]
+async fn waits_for_a_free_slot_over_max_connections() {
+    let mut server = Server::new();
+    server.max_connections(1);
+    let running = start_hello(server).await;
+
+    let mut first = TcpStream::connect(running.addr).await.unwrap();
+    assert!(fetch_hello(&mut first).await.starts_with("HTTP/1.1 200"));
+    let mut second = TcpStream::connect(running.addr).await.unwrap();
+    assert!(timeout(Duration::from_millis(200), fetch_hello(&mut second)).await.is_err());
+
+    drop(first);
+    assert!(fetch_hello(&mut second).await.starts_with("HTTP/1.1 200"));
+}
+
+/// Accepts one connection closed right away, then fails every accept
+struct Failing {
+    accepted: bool,
+    errors: Arc<Mutex<Vec<Instant>>>,
+}
+
+impl Listener for Failing {
+    type Stream = DuplexStream;
+    fn poll_accept(&mut self, _: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>> {
+        if !self.accepted {
+            self.accepted = true;
+            return Poll::Ready(Ok((duplex(64).0, SocketAddr::from(([127, 0, 0, 1], 1)))));
+        }
+        self.errors.lock().unwrap().push(Instant::now());
+        Poll::Ready(Err(io::Error::other("too many open files")))
+    }
+}
+
+#[tokio::test]
+async fn keeps_backing_off_when_a_connection_finishes() {
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let listener = Failing { accepted: false, errors: errors.clone() };
+    let mut server = Server::new();
+    server.accept_backoff(AcceptBackoff { min: Duration::from_millis(20), max: Duration::from_millis(80) });
+    server.events(ServerEventsBuilder::new().on_connection_error(|_| {}));
+    let (shutdown, serving) = spawn(|signal| server.serve_listener_with_shutdown(listener, Router::new(), signal));
+
+    sleep(Duration::from_millis(300)).await;
+    shutdown.send(()).unwrap();
+    serving.await.unwrap();
+
+    let errors = errors.lock().unwrap();
+    assert!(errors.len() >= 3, "{} accept errors", errors.len());
+    for pair in errors.windows(2) {
+        assert!(pair[1] - pair[0] >= Duration::from_millis(19), "retried after {:?}", pair[1] - pair[0]);
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{future::poll_fn, io, net::SocketAddr, path::PathBuf, task::{Context, Poll}, time::Duration};
+use common::{fetch, raw_get, spawn};
+use http_tokio_router::{Router, node::*, route, server::{Listener, Server}};
+use tokio::{io::{duplex, AsyncRead, AsyncWrite, DuplexStream}, net::{TcpListener, TcpStream, UnixListener, UnixStream}};
+
+#[route]
+async fn hello() -> &'static str {
+    "hello"
+}
+
+/// Always has a connection ready, tagged with its port
+struct Ready(u16);
+
+impl Listener for Ready {
+    type Stream = DuplexStream;
+    fn poll_accept(&mut self, _: &mut Context<'_>) -> Poll<io::Result<(Self::Stream, SocketAddr)>> {
+        Poll::Ready(Ok((duplex(64).0, SocketAddr::from(([127, 0, 0, 1], self.0)))))
+    }
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("http-tokio-router-{name}-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn fetch_hello<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, close: bool) -> String {
+    fetch(stream, &raw_get("/hello", close), "hello").await
+}
+
+#[tokio::test]
+async fn takes_turns_between_ready_listeners() {
+    let mut listeners = vec![Ready(1), Ready(2), Ready(3)];
+    let mut ports = Vec::new();
+    for _ in 0..6 {
+        let (_, addr) = poll_fn(|cx| listeners.poll_accept(cx)).await.unwrap();
+        ports.push(addr.port());
+    }
+    assert_eq!(ports, [1, 2, 3, 1, 2, 3]);
+}
+
+#[tokio::test]
+async fn serves_tcp_and_unix_listeners_together() {
+    let tcp = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let tcp_addr = tcp.local_addr().unwrap();
+    let path = socket_path("mixed");
+    let unix = UnixListener::bind(&path).unwrap();
+    let listeners = vec![tcp.boxed(), unix.boxed()];
+    let (shutdown, server) = spawn(|signal| Server::new().serve_listener_with_shutdown(listeners, Router::new().at("/hello", get(hello)), signal));
+
+    let tcp_res = fetch_hello(&mut TcpStream::connect(tcp_addr).await.unwrap(), true).await;
+    let unix_res = fetch_hello(&mut UnixStream::connect(&path).await.unwrap(), true).await;
+    assert!(tcp_res.starts_with("HTTP/1.1 200") && unix_res.starts_with("HTTP/1.1 200"), "{tcp_res}\n{unix_res}");
+
+    shutdown.send(()).unwrap();
+    server.await.unwrap();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn unix_peers_skip_the_per_ip_limit() {
+    let path = socket_path("per-ip");
+    let unix = UnixListener::bind(&path).unwrap();
+    let mut server = Server::new();
+    server.max_connections_per_ip(1).drain_timeout(Duration::from_secs(1));
+    let (shutdown, server) = spawn(|signal| server.serve_listener_with_shutdown(unix, Router::new().at("/hello", get(hello)), signal));
+
+    let mut first = UnixStream::connect(&path).await.unwrap();
+    let mut second = UnixStream::connect(&path).await.unwrap();
+    assert!(fetch_hello(&mut first, false).await.starts_with("HTTP/1.1 200"));
+    assert!(fetch_hello(&mut second, false).await.starts_with("HTTP/1.1 200"));
+
+    shutdown.send(()).unwrap();
+    assert_eq!(server.await.unwrap().idle_closed, 2);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, node::*, extractors::MatchedRoute, route, test_client::TestClient};
+
+/// Answers with the matched route as `pattern method name [scopes]`
+#[route]
+async fn describe(route: MatchedRoute) -> String {
+    let scopes: Vec<String> = route.scopes().iter()
+        .map(|scope| format!("{}:{}", scope.pattern, scope.name.as_deref().unwrap_or("-")))
+        .collect();
+    let (method, name) = (route.method().unwrap_or("-"), route.name().unwrap_or("-"));
+    format!("{} {method} {name} [{}]", route.pattern(), scopes.join(", "))
+}
+
+#[tokio::test]
+async fn reports_the_scopes_of_nested_routes() {
+    let users = scope("/users").name("users").at("/{id}", get(describe));
+    let router = Router::new().add(scope("/api").name("api").add(users));
+    let client = TestClient::new(router.build().unwrap());
+
+    client.get("/api/users/7").send().await.assert_status(200).assert_text("/api/users/{id} GET - [ALL:/api:api, ALL:/users:users, ALL:/{id}:-]");
+}
+
+#[tokio::test]
+async fn reports_the_name_of_named_routes() {
+    let router = Router::new()
+        .at("/users/{id}", get(describe).name("user.show"))
+        .at("/about", describe);
+    let client = TestClient::new(router.build().unwrap());
+
+    client.get("/users/7").send().await.assert_text("/users/{id} GET user.show [ALL:/users/{id}:-]");
+    client.post("/about").send().await.assert_text("/about - - []");
+}
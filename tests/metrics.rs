@@ -0,0 +1,41 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, metrics::Metrics, node::*, route, test_client::TestClient};
+
+#[route]
+async fn user() -> &'static str {
+    "user"
+}
+
+fn count(rendered: &str, labels: &str) -> Option<u64> {
+    let prefix = format!("http_requests_total{{{labels}}} ");
+    rendered.lines().find_map(|line| line.strip_prefix(&prefix)?.parse().ok())
+}
+
+#[tokio::test]
+async fn records_matched_and_unmatched_requests() {
+    let metrics = Metrics::new();
+    let api = Router::new().at("/users/{id}", get(user));
+    let router = Router::new()
+        .wrap(metrics.clone())
+        .host("api.example.com", api)
+        .at("/users/{id}", get(user))
+        .at("/metrics", get(metrics.clone()));
+    let client = TestClient::new(router);
+
+    client.get("/users/1").send().await.assert_status(200);
+    client.get("/users/2").send().await.assert_status(200);
+    client.get("/users/3").header("Host", "api.example.com").send().await.assert_status(200);
+    client.get("/nope").send().await.assert_status(404);
+    client.post("/users/1").send().await.assert_status(405);
+    client.options("/users/1").send().await.assert_status(204);
+    client.request("PURGE", "/users/1").send().await.assert_status(405);
+
+    let rendered = client.get("/metrics").send().await.assert_status(200).text();
+    assert_eq!(count(&rendered, r#"method="GET",pattern="/users/{id}",status="200""#), Some(3));
+    assert_eq!(count(&rendered, r#"method="GET",pattern="unmatched",status="404""#), Some(1));
+    assert_eq!(count(&rendered, r#"method="POST",pattern="unmatched",status="405""#), Some(1));
+    assert_eq!(count(&rendered, r#"method="OPTIONS",pattern="unmatched",status="204""#), Some(1));
+    assert_eq!(count(&rendered, r#"method="OTHER",pattern="unmatched",status="405""#), Some(1));
+    assert!(rendered.contains(r#"http_requests_in_flight{method="GET",pattern="/metrics"} 1"#), "{rendered}");
+}
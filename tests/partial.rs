@@ -0,0 +1,71 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::tag;
+use http_tokio_router::{Router, node::*, pattern::Pattern, test_client::TestClient};
+use serde_json::json;
+
+#[tokio::test]
+async fn captures_parts_of_segments() {
+    let router = Router::new()
+        .at("/files/{name}.{ext}", get(tag("file")).name("file"))
+        .at("/files/{name}", get(tag("plain")))
+        .at("/v{version:int}/items", get(tag("items")))
+        .at("/@{username}", get(tag("user")))
+        .at("/archive/{year:\\d{4}}-{month:[0-9]{2}}", get(tag("archive")))
+        .at("/price/{amount:\\d+\\$}-{currency}", get(tag("price")))
+        .at("/about.html", get(tag("static")))
+        .build()
+        .unwrap();
+    assert_eq!(router.url_for("file", &json!({"name": "a b", "ext": "txt"})).unwrap(), "/files/a%20b.txt");
+    let client = TestClient::new(router);
+
+    client.get("/files/a.tar.gz").send().await.assert_text("file name=a,ext=tar.gz");
+    client.get("/files/readme").send().await.assert_text("plain name=readme");
+    client.get("/v2/items").send().await.assert_text("items version=2");
+    client.get("/vx/items").send().await.assert_status(404);
+    client.get("/@bob").send().await.assert_text("user username=bob");
+    client.get("/archive/2024-05").send().await.assert_text("archive year=2024,month=05");
+    client.get("/archive/24-05").send().await.assert_status(404);
+    client.get("/price/5$-usd").send().await.assert_text("price amount=5$,currency=usd");
+    client.get("/about.html").send().await.assert_text("static");
+}
+
+#[tokio::test]
+async fn keeps_groups_of_constraints_apart_from_the_captures() {
+    let router = Router::new()
+        .at("/r/{from:(?P<p0>[a-z])+}-{to:(a|b)(c)}.{ext}", get(tag("range")))
+        .build()
+        .unwrap();
+    let client = TestClient::new(router);
+
+    client.get("/r/xy-bc.txt").send().await.assert_text("range from=xy,to=bc,ext=txt");
+    client.get("/r/xy-cc.txt").send().await.assert_status(404);
+}
+
+#[test]
+fn rejects_anchored_constraints() {
+    assert!(Pattern::parse("/v{version:^\\d+}").is_err());
+    assert!(Pattern::parse("/{name:[a-z]+$}.{ext}").is_err());
+    assert!(Pattern::parse("/{name:(?m)^a}").is_err());
+    assert!(Pattern::parse("/{price:\\d+\\$}").is_ok());
+}
+
+#[test]
+fn rejects_invalid_partial_segments() {
+    assert!(Pattern::parse("/a/{x}{y}").is_err());
+    assert!(Pattern::parse("/a/{x").is_err());
+    assert!(Pattern::parse("/a/x}").is_err());
+    assert!(Pattern::parse("/y/{year:\\d{4}}-{m}").is_ok());
+}
+
+#[test]
+fn rejects_dot_segments_and_bare_at_signs() {
+    assert!(Pattern::parse("/a/./b").is_err());
+    assert!(Pattern::parse("/a/..").is_err());
+    assert!(Pattern::parse("/a/.well-known").is_ok());
+    assert!(Pattern::parse("/@admin").is_err());
+    assert!(Pattern::parse("/{user@host}").is_err());
+    assert!(Pattern::parse("/@{username}").is_ok());
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, node::*, extractors::Path, route, test_client::TestClient};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Item {
+    id: u32,
+    name: String,
+    kind: Option<Kind>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    A,
+    B,
+}
+
+#[route]
+async fn item(path: Path<Item>) -> String {
+    format!("{} {} {:?}", path.name, path.id, path.kind)
+}
+
+#[route]
+async fn pair(path: Path<(String, u8)>) -> String {
+    format!("{:?}", path.into_inner())
+}
+
+#[route]
+async fn single(path: Path<u64>) -> String {
+    format!("{:?}", path.into_inner())
+}
+
+fn router() -> Router {
+    Router::new()
+        .at("/items/{name}/{id}", get(item))
+        .at("/items/{name}/{id}/{kind}", get(item))
+        .at("/pairs/{z}/{a}", get(pair))
+        .at("/single/{v}", get(single))
+}
+
+#[tokio::test]
+async fn deserializes_structs_by_name() {
+    let client = TestClient::new(router());
+    client.get("/items/bob/3").send().await.assert_status(200).assert_text("bob 3 None");
+    client.get("/items/bob/3/a").send().await.assert_status(200).assert_text("bob 3 Some(A)");
+    client.get("/items/bob/x").send().await.assert_status(400);
+    client.get("/items/bob/3/c").send().await.assert_status(400);
+}
+
+#[tokio::test]
+async fn deserializes_tuples_and_scalars_in_pattern_order() {
+    let client = TestClient::new(router());
+    client.get("/pairs/q/7").send().await.assert_status(200).assert_text(r#"("q", 7)"#);
+    client.get("/pairs/q/700").send().await.assert_status(400);
+    client.get("/single/5").send().await.assert_status(200).assert_text("5");
+    client.get("/single/-1").send().await.assert_status(400);
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, node::*, extractors::Query, route, test_client::TestClient};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Search {
+    #[serde(default)]
+    tag: Vec<String>,
+    page: Option<u32>,
+    name: String,
+}
+
+#[route]
+async fn search(query: Query<Search>) -> String {
+    format!("{} {:?} {:?}", query.name, query.tag, query.page)
+}
+
+#[route]
+async fn strict(query: Query<Search, 422>) -> String {
+    query.into_inner().name
+}
+
+#[tokio::test]
+async fn deserializes_decoded_query_strings() {
+    let client = TestClient::new(Router::new().at("/search", get(search)));
+    client.get("/search?tag=a&tag=b%20c&name=x+y").send().await.assert_status(200).assert_text(r#"x y ["a", "b c"] None"#);
+    client.get("/search?name=z&page=2").send().await.assert_status(200).assert_text("z [] Some(2)");
+    client.get("/search?name=z&page=no").send().await.assert_status(400);
+    client.get("/search?page=1").send().await.assert_status(400);
+}
+
+#[tokio::test]
+async fn routes_on_the_path_alone_and_uses_the_given_status() {
+    let client = TestClient::new(Router::new().at("/strict/{id}", get(strict)));
+    client.get("/strict/1?name=z").send().await.assert_status(200).assert_text("z");
+    client.get("/strict/1?page=1").send().await.assert_status(422);
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio_router::{Router, node::*, response::{Html, Json, Redirect}, route, test_client::TestClient};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct User {
+    id: u32,
+}
+
+#[route]
+async fn created() -> (u16, [(&'static str, &'static str); 1], Json<User>) {
+    (201, [("X-Id", "1")], Json(User { id: 1 }))
+}
+
+#[route]
+async fn page() -> Html<String> {
+    Html(format!("<b>{}</b>", 1))
+}
+
+#[route]
+async fn moved() -> Redirect {
+    Redirect::see_other("/page")
+}
+
+#[route]
+async fn nothing() {}
+
+#[route]
+async fn teapot() -> (u16, String) {
+    (418, "tea".to_string())
+}
+
+#[tokio::test]
+async fn builds_responses_from_return_values() {
+    let router = Router::new()
+        .at("/created", post(created))
+        .at("/page", get(page))
+        .at("/moved", get(moved))
+        .at("/nothing", get(nothing))
+        .at("/teapot", get(teapot));
+    let client = TestClient::new(router);
+
+    client.post("/created").send().await
+        .assert_status(201)
+        .assert_header("X-Id", "1")
+        .assert_header("Content-Type", "application/json")
+        .assert_json(&serde_json::json!({"id": 1}));
+    client.get("/page").send().await
+        .assert_status(200)
+        .assert_header("Content-Type", "text/html; charset=utf-8")
+        .assert_text("<b>1</b>");
+    client.get("/moved").send().await.assert_status(303).assert_header("Location", "/page");
+    client.get("/nothing").send().await.assert_status(204).assert_text("");
+    client.get("/teapot").send().await.assert_status(418).assert_text("tea");
+}
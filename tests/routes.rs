@@ -0,0 +1,59 @@
+use std::sync::Arc;
+use futures::future::BoxFuture;
+use http_tokio::{BodyReader, Request, Response};
+use http_tokio_router::{Router, RouteInfo, middleware::{Middleware, Next}, node::*, route};
+
+#[route]
+async fn ok() -> &'static str {
+    "ok"
+}
+
+#[derive(Debug)]
+struct Audit;
+
+impl Middleware for Audit {
+    fn handle<'a>(self: Arc<Self>, _: &'a Request, _: &'a BodyReader, next: Next<'a>) -> BoxFuture<'a, Response> {
+        next()
+    }
+}
+
+fn row(route: &RouteInfo) -> (Option<&str>, Option<&str>, &str, Option<&str>, Vec<&str>) {
+    let middlewares = route.middlewares.iter().map(String::as_str).collect();
+    (route.host.as_deref(), route.method.as_deref(), &route.pattern, route.name.as_deref(), middlewares)
+}
+
+#[test]
+fn lists_routes_across_scopes() {
+    let router = Router::new()
+        .wrap(Audit)
+        .at("/users/{id}", get(ok).name("user.show"))
+        .at("/api", scope("/").add(post(ok)).at("/*", ok));
+    let routes: Vec<RouteInfo> = router.routes().collect();
+    let rows: Vec<_> = routes.iter().map(row).collect();
+    assert_eq!(rows, [
+        (None, Some("GET"), "/users/{id}", Some("user.show"), vec!["Audit"]),
+        (None, Some("POST"), "/api", None, vec!["Audit"]),
+        (None, None, "/api/*", None, vec!["Audit"]),
+    ]);
+
+    let table = router.routes().to_string();
+    assert!(table.starts_with("METHOD  PATTERN"), "{table}");
+    assert!(table.contains("GET     /users/{id}  user.show  Audit"), "{table}");
+}
+
+#[test]
+fn lists_virtual_host_routes_with_their_host() {
+    let router = Router::new()
+        .host("{tenant}.example.com", Router::new().at("/dashboard", get(ok)))
+        .at("/", get(ok));
+    let routes: Vec<RouteInfo> = router.routes().collect();
+    let rows: Vec<_> = routes.iter().map(row).collect();
+    assert_eq!(rows, [
+        (None, Some("GET"), "/", None, vec![]),
+        (Some("{tenant}.example.com"), Some("GET"), "/dashboard", None, vec![]),
+    ]);
+
+    let table = router.routes().to_string();
+    assert!(table.starts_with("HOST                  METHOD  PATTERN"), "{table}");
+    assert!(table.contains("{tenant}.example.com  GET     /dashboard"), "{table}");
+}
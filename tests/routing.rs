@@ -0,0 +1,140 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use http_tokio::{BodyReader, Request};
+use common::tag;
+use http_tokio_router::{Router, node::*, test_client::TestClient};
+
+async fn text(client: &TestClient, method: &str, path: &str) -> (u16, String) {
+    let res = client.request(method, path).send().await;
+    (res.status().as_u16(), res.text())
+}
+
+#[tokio::test]
+async fn routes_static_dynamic_and_wildcard_segments() {
+    let router = Router::new()
+        .at("/users/{id}", get(tag("user")))
+        .at("/users/me", get(tag("me")))
+        .at("/files/*", get(tag("files")))
+        .at("/api", scope("/v1").at("/items/{id}", post(tag("item"))).add(get(tag("v1"))));
+    let client = TestClient::new(router);
+
+    assert_eq!(text(&client, "GET", "/users/5").await, (200, "user id=5".into()));
+    assert_eq!(text(&client, "GET", "/users/me").await, (200, "me".into()));
+    assert_eq!(text(&client, "GET", "/files/a/b").await, (200, "files *=a/b".into()));
+    assert_eq!(text(&client, "GET", "/files").await, (200, "files *=".into()));
+    assert_eq!(text(&client, "POST", "/api/v1/items/3").await, (200, "item id=3".into()));
+    assert_eq!(text(&client, "GET", "/api/v1").await, (200, "v1".into()));
+    assert_eq!(text(&client, "GET", "/nope").await.0, 404);
+}
+
+#[tokio::test]
+async fn backtracks_to_less_specific_routes() {
+    let router = Router::new()
+        .at("/users/*", get(tag("wild")))
+        .at("/users/{id}/posts", get(tag("posts")))
+        .at("/users/me", get(tag("me")))
+        .at("/users/me", post(tag("post me")))
+        .at("/users", get(tag("list")));
+    let client = TestClient::new(router);
+
+    assert_eq!(text(&client, "GET", "/users/me/posts").await, (200, "posts id=me".into()));
+    assert_eq!(text(&client, "GET", "/users/me").await, (200, "me".into()));
+    assert_eq!(text(&client, "POST", "/users/me").await, (200, "post me".into()));
+    assert_eq!(text(&client, "GET", "/users/x/y/z").await, (200, "wild *=x/y/z".into()));
+    assert_eq!(text(&client, "GET", "/users/x").await, (200, "wild *=x".into()));
+    assert_eq!(text(&client, "GET", "/users").await, (200, "list".into()));
+}
+
+#[tokio::test]
+async fn ranks_static_over_params_over_wildcards_in_any_order() {
+    let routes = [("/p/*", "wild"), ("/p/{id}", "param"), ("/p/new", "static")];
+    for order in [[0, 1, 2], [2, 1, 0], [1, 0, 2]] {
+        let router = order.iter().fold(Router::new(), |router, &i| router.at(routes[i].0, get(tag(routes[i].1))));
+        let client = TestClient::new(router.build().unwrap());
+
+        assert_eq!(text(&client, "GET", "/p/new").await, (200, "static".into()), "{order:?}");
+        assert_eq!(text(&client, "GET", "/p/7").await, (200, "param id=7".into()), "{order:?}");
+        assert_eq!(text(&client, "GET", "/p/7/8").await, (200, "wild *=7/8".into()), "{order:?}");
+    }
+}
+
+#[tokio::test]
+async fn backtracks_when_a_more_specific_subtree_has_no_handler() {
+    let router = Router::new()
+        .at("/u/me/settings", get(tag("settings")))
+        .at("/u/{id}/posts", get(tag("posts")))
+        .at("/u/{id}", post(tag("update")))
+        .at("/u/*", get(tag("wild")))
+        .build()
+        .unwrap();
+    let client = TestClient::new(router);
+
+    assert_eq!(text(&client, "GET", "/u/me/settings").await, (200, "settings".into()));
+    assert_eq!(text(&client, "GET", "/u/me/posts").await, (200, "posts id=me".into()));
+    assert_eq!(text(&client, "POST", "/u/me").await, (200, "update id=me".into()));
+    assert_eq!(text(&client, "GET", "/u/me").await, (200, "wild *=me".into()));
+    assert_eq!(text(&client, "GET", "/u/7/comments").await, (200, "wild *=7/comments".into()));
+}
+
+#[test]
+fn build_reports_every_invalid_route() {
+    let errors = Router::new()
+        .at("/users/{id}", get(tag("a")))
+        .at("/users/{user_id}", get(tag("b")))
+        .at("bad", get(tag("c")))
+        .at("/x", get(tag("d")))
+        .add(scope("/x").add(get(tag("e"))))
+        .at("/f/*", scope("/g").add(get(tag("f"))))
+        .add(scope("GET:/m").at("/n", post(tag("g"))))
+        .build()
+        .err()
+        .unwrap();
+    let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(errors.len(), 6, "{errors:#?}");
+    assert!(errors.iter().any(|err| err == "POST:/m/n nested in a GET route can never match"), "{errors:#?}");
+
+    assert!(Router::new().try_at("nope", get(tag("x"))).is_err());
+    assert!(Router::new().at("/a", get(tag("x"))).at("/a", post(tag("x"))).build().is_ok());
+}
+
+#[tokio::test]
+async fn answers_405_with_allowed_methods() {
+    let router = Router::new()
+        .at("/a/{id}", get(tag("get")))
+        .at("/a/{id}", delete(tag("delete")))
+        .build()
+        .unwrap();
+    let client = TestClient::new(router);
+    client.post("/a/1").send().await.assert_status(405).assert_header("Allow", "DELETE, GET, HEAD, OPTIONS");
+    client.post("/b").send().await.assert_status(404);
+}
+
+#[tokio::test]
+async fn custom_405_handler_keeps_the_allow_header() {
+    let router = Router::new()
+        .at("/a", get(tag("get")))
+        .set_method_not_allowed_handler(async |_: &Request, _: &BodyReader| {
+            http_tokio_router::result::IntoRouteResult::into((405u16, "nope"))
+        });
+    let client = TestClient::new(router);
+    client.post("/a").send().await.assert_status(405).assert_header("Allow", "GET, HEAD, OPTIONS").assert_text("nope");
+}
+
+#[tokio::test]
+async fn answers_head_and_options_on_its_own() {
+    let router = Router::new()
+        .at("/a", get(tag("get")))
+        .at("/a", post(tag("post")))
+        .at("/b", get(tag("get")))
+        .at("/b", options(tag("options")));
+    let client = TestClient::new(router);
+
+    let res = client.head("/a").send().await;
+    res.assert_status(200).assert_header("Content-Length", "3");
+    assert!(res.bytes().is_empty());
+    client.options("/a").send().await.assert_status(204).assert_header("Allow", "GET, HEAD, OPTIONS, POST");
+    assert_eq!(text(&client, "OPTIONS", "/b").await, (200, "options".into()));
+    client.options("/c").send().await.assert_status(404);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use std::time::Duration;
+use common::{fetch, raw_get, read_to_end, start, Running};
+use http_tokio_router::{Router, node::*, route, server::Server};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream}, time::sleep};
+
+#[route]
+async fn fast() -> &'static str {
+    "fast"
+}
+
+#[route]
+async fn slow() -> &'static str {
+    sleep(Duration::from_millis(200)).await;
+    "slow"
+}
+
+#[route]
+async fn hang() -> &'static str {
+    sleep(Duration::from_secs(3600)).await;
+    "never"
+}
+
+async fn start_draining(drain_timeout: Duration) -> Running {
+    let router = Router::new().at("/fast", get(fast)).at("/slow", get(slow)).at("/hang", get(hang));
+    let mut server = Server::new();
+    server.drain_timeout(drain_timeout);
+    start(server, router).await
+}
+
+#[tokio::test]
+async fn closes_idle_keep_alive_connections() {
+    let running = start_draining(Duration::from_secs(5)).await;
+    let mut client = TcpStream::connect(running.addr).await.unwrap();
+    fetch(&mut client, &raw_get("/fast", false), "fast").await;
+
+    let summary = running.stop().await;
+    assert_eq!((summary.drained, summary.idle_closed, summary.force_closed.len()), (0, 1, 0));
+    assert_eq!(read_to_end(&mut client).await, "");
+}
+
+#[tokio::test]
+async fn finishes_in_flight_requests_with_connection_close() {
+    let running = start_draining(Duration::from_secs(5)).await;
+    let mut client = TcpStream::connect(running.addr).await.unwrap();
+    client.write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+    sleep(Duration::from_millis(50)).await;
+
+    running.shutdown.send(()).unwrap();
+    let res = read_to_end(&mut client).await;
+    assert!(res.starts_with("HTTP/1.1 200") && res.contains("Connection: close") && res.ends_with("slow"), "{res}");
+    let summary = running.server.await.unwrap();
+    assert_eq!((summary.drained, summary.idle_closed), (1, 0));
+}
+
+#[tokio::test]
+async fn waits_for_partially_received_requests() {
+    let running = start_draining(Duration::from_secs(5)).await;
+    let mut client = TcpStream::connect(running.addr).await.unwrap();
+    client.write_all(b"GET /fast HTTP/1.1\r\n").await.unwrap();
+    sleep(Duration::from_millis(50)).await;
+
+    running.shutdown.send(()).unwrap();
+    sleep(Duration::from_millis(50)).await;
+    client.write_all(b"Host: localhost\r\n\r\n").await.unwrap();
+    let res = read_to_end(&mut client).await;
+    assert!(res.starts_with("HTTP/1.1 200") && res.ends_with("fast"), "{res}");
+    assert_eq!(running.server.await.unwrap().drained, 1);
+}
+
+#[tokio::test]
+async fn force_closes_connections_after_the_drain_timeout() {
+    let running = start_draining(Duration::from_millis(100)).await;
+    let mut client = TcpStream::connect(running.addr).await.unwrap();
+    client.write_all(b"GET /hang HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+    sleep(Duration::from_millis(50)).await;
+
+    let summary = running.stop().await;
+    assert_eq!(summary.force_closed, vec![client.local_addr().unwrap()]);
+    assert_eq!(read_to_end(&mut client).await, "");
+}
+
+#[tokio::test]
+async fn refuses_to_serve_an_invalid_router() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let router = Router::new().at("/a/{x", get(fast));
+    let err = Server::new().serve_listener_with_shutdown(listener, router, std::future::pending()).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("/a/{x"), "{err}");
+}
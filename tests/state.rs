@@ -0,0 +1,33 @@
+#![cfg(feature = "test-util")]
+
+use http_tokio::{BodyReader, Request};
+use http_tokio_router::{Router, node::*, extractors::{FromRequest, State}, route, test_client::TestClient};
+
+#[route]
+async fn show(req: &Request, body: &BodyReader) -> String {
+    let name = State::<String>::from_req(req, body).await.map(|name| name.to_string());
+    let count = State::<u32>::from_req(req, body).await.map(|count| *count);
+    format!("{} {:?}", name.unwrap_or_default(), count.ok())
+}
+
+#[route]
+async fn spawned(name: State<String>) -> String {
+    tokio::spawn(async move { name.to_uppercase() }).await.unwrap()
+}
+
+#[tokio::test]
+async fn resolves_states_of_the_matched_scope() {
+    let router = Router::new()
+        .with_state("app".to_string())
+        .at("/a", get(show))
+        .at("/b", scope("/").with_state(7u32).with_state("inner".to_string()).at("/c", get(show)));
+    let client = TestClient::new(router);
+    client.get("/a").send().await.assert_status(200).assert_text("app None");
+    client.get("/b/c").send().await.assert_status(200).assert_text("inner Some(7)");
+}
+
+#[tokio::test]
+async fn states_move_into_spawned_tasks() {
+    let client = TestClient::new(Router::new().with_state("app".to_string()).at("/", get(spawned)));
+    client.get("/").send().await.assert_status(200).assert_text("APP");
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "tls")]
+
+mod common;
+
+use std::{sync::{Arc, Mutex}, time::Duration};
+use common::{fetch, raw_get, read_to_end, start_with, Running};
+use http_tokio_router::{Router, node::*, route, server::{tls::{self, rustls}, Server, ServerEventsBuilder}};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+#[route]
+async fn hello() -> &'static str {
+    "hello"
+}
+
+#[derive(Default)]
+struct Seen {
+    opened: usize,
+    handshake_errors: Vec<std::io::ErrorKind>,
+}
+
+async fn start() -> (Running, Arc<Mutex<Seen>>) {
+    let config = tls::server_config(format!("{FIXTURES}/cert.pem"), format!("{FIXTURES}/key.pem")).unwrap();
+    let seen = Arc::new(Mutex::new(Seen::default()));
+    let (opened, errors) = (seen.clone(), seen.clone());
+
+    let mut server = Server::new();
+    server.tls_handshake_timeout(Duration::from_millis(100));
+    server.events(ServerEventsBuilder::new()
+        .on_connection_opened(move |_| opened.lock().unwrap().opened += 1)
+        .on_tls_handshake_error(move |_, err| errors.lock().unwrap().handshake_errors.push(err.kind())));
+    let router = Router::new().at("/hello", get(hello));
+    let running = start_with(|listener, signal| server.serve_tls_listener_with_shutdown(listener, config, router, signal)).await;
+    (running, seen)
+}
+
+fn connector() -> TlsConnector {
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in tls::load_certs(format!("{FIXTURES}/ca.pem")).unwrap() {
+        roots.add(cert).unwrap();
+    }
+    let config = rustls::ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+#[tokio::test]
+async fn serves_https_and_drains_on_shutdown() {
+    let (running, seen) = start().await;
+    let stream = TcpStream::connect(running.addr).await.unwrap();
+    let server_name = rustls::pki_types::ServerName::try_from("localhost").unwrap();
+    let mut stream = connector().connect(server_name, stream).await.unwrap();
+    assert!(fetch(&mut stream, &raw_get("/hello", false), "hello").await.starts_with("HTTP/1.1 200"));
+
+    let summary = running.stop().await;
+    assert_eq!((summary.idle_closed, summary.force_closed.len()), (1, 0));
+    assert_eq!(seen.lock().unwrap().opened, 1);
+}
+
+#[tokio::test]
+async fn drops_clients_stalling_the_handshake() {
+    let (running, seen) = start().await;
+    let mut stalled = TcpStream::connect(running.addr).await.unwrap();
+    assert_eq!(read_to_end(&mut stalled).await, "");
+
+    let summary = running.stop().await;
+    assert_eq!((summary.drained, summary.idle_closed, summary.force_closed.len()), (0, 0, 0));
+    let seen = seen.lock().unwrap();
+    assert_eq!((seen.opened, seen.handshake_errors.as_slice()), (0, [std::io::ErrorKind::TimedOut].as_slice()));
+}
+
+#[tokio::test]
+async fn picks_the_certificate_by_server_name() {
+    let config = tls::SniCertificates::new()
+        .add("localhost", format!("{FIXTURES}/cert.pem"), format!("{FIXTURES}/key.pem"))
+        .and_then(|certs| certs.add("Example.test", format!("{FIXTURES}/example-cert.pem"), format!("{FIXTURES}/example-key.pem")))
+        .unwrap()
+        .into_config();
+    let router = Router::new().at("/hello", get(hello));
+    let running = start_with(|listener, signal| Server::new().serve_tls_listener_with_shutdown(listener, config, router, signal)).await;
+
+    for (name, cert) in [("localhost", "cert.pem"), ("example.test", "example-cert.pem")] {
+        let stream = TcpStream::connect(running.addr).await.unwrap();
+        let server_name = rustls::pki_types::ServerName::try_from(name).unwrap();
+        let mut stream = connector().connect(server_name, stream).await.unwrap();
+        let expected = tls::load_certs(format!("{FIXTURES}/{cert}")).unwrap();
+        assert_eq!(stream.get_ref().1.peer_certificates(), Some(expected.as_slice()), "{name}");
+        assert!(fetch(&mut stream, &raw_get("/hello", true), "hello").await.starts_with("HTTP/1.1 200"));
+    }
+    running.stop().await;
+}
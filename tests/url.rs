@@ -0,0 +1,45 @@
+#![cfg(feature = "test-util")]
+
+use std::collections::HashMap;
+use http_tokio_router::{Router, node::*, error::{RegisterError, UrlError}, extractors::UrlFor, route, test_client::TestClient};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct UserParams {
+    id: u32,
+}
+
+/// Answers with the url of the `user.show` route for the id `a b/c`
+#[route]
+async fn link(url_for: UrlFor) -> String {
+    url_for.url("user.show", &HashMap::from([("id", "a b/c")])).unwrap_or_else(|err| err.to_string())
+}
+
+fn router() -> Router {
+    Router::new()
+        .at("/users/{id}", get(link).name("user.show"))
+        .at("/files/*", get(link).name("files"))
+        .at("/", get(link).name("home"))
+}
+
+#[tokio::test]
+async fn builds_urls_of_named_routes() {
+    let router = router();
+    assert_eq!(router.url_for("user.show", &UserParams { id: 4 }).unwrap(), "/users/4");
+    assert_eq!(router.url_for("files", &HashMap::from([("*", "a/b c")])).unwrap(), "/files/a/b%20c");
+    assert_eq!(router.url_for("home", &()).unwrap(), "/");
+    assert!(matches!(router.url_for("user.show", &()), Err(UrlError::MissingParam(param)) if param == "id"));
+    assert!(matches!(router.url_for("nope", &()), Err(UrlError::UnknownRoute(name)) if name == "nope"));
+}
+
+#[tokio::test]
+async fn builds_urls_from_handlers() {
+    let client = TestClient::new(router());
+    client.get("/users/1").send().await.assert_status(200).assert_text("/users/a%20b%2Fc");
+}
+
+#[tokio::test]
+async fn reports_duplicated_route_names() {
+    let errors = Router::new().at("/a", get(link).name("x")).at("/b", get(link).name("x")).build().err().unwrap();
+    assert!(matches!(&errors[..], [RegisterError::DuplicateRouteName(name)] if name == "x"), "{errors:?}");
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::tag;
+use http_tokio_router::{Router, node::*, error::RegisterError, test_client::TestClient};
+
+#[tokio::test]
+async fn routes_by_host_before_path() {
+    let router = Router::new()
+        .host("{tenant}.example.com", Router::new().at("/", get(tag("tenant"))))
+        .host("api.example.com", Router::new().at("/", get(tag("api"))))
+        .at("/", get(tag("default")))
+        .build()
+        .unwrap();
+    let client = TestClient::new(router);
+
+    client.get("/").header("Host", "api.example.com").send().await.assert_text("api");
+    client.get("/").header("Host", "Acme.example.com:80").send().await.assert_text("tenant tenant=acme");
+    client.head("/").header("Host", "acme.example.com").send().await.assert_status(200);
+    client.get("/").header("Host", "other.org").send().await.assert_text("default");
+    client.get("/").send().await.assert_text("default");
+    client.get("/x").header("Host", "api.example.com").send().await.assert_status(404);
+}
+
+#[tokio::test]
+async fn reports_invalid_and_nested_hosts() {
+    let errors = Router::new().host("a..b", Router::new()).build().err().unwrap();
+    assert!(matches!(&errors[..], [RegisterError::InvalidPattern(pattern, _)] if pattern == "a..b"), "{errors:?}");
+
+    let nested = Router::new().host("eu.api.example.com", Router::new());
+    let errors = Router::new().host("api.example.com", nested).build().err().unwrap();
+    assert!(matches!(&errors[..], [RegisterError::NestedVirtualHost(host)] if host == "api.example.com"), "{errors:?}");
+}